@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::{config_path, Config};
+use crate::utils::{check_cargo_available, check_git_available};
+
+/// How long the GitHub reachability check waits for an answer
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of one environment check
+struct Check {
+    name: &'static str,
+    /// What was found, or why the check failed
+    result: Result<String>,
+    /// What to do about a failure
+    hint: String,
+    /// A failed critical check makes `fbcli doctor` exit non-zero
+    critical: bool,
+}
+
+pub async fn run() -> Result<()> {
+    statusln!("🔍 Checking your environment...");
+    outln!();
+
+    let checks = [
+        Check {
+            name: "git",
+            result: check_git_available().and_then(|()| tool_version("git")),
+            hint: "Install git from https://git-scm.com/downloads and make sure it's in PATH".to_string(),
+            critical: true,
+        },
+        Check {
+            name: "cargo",
+            result: check_cargo_available().and_then(|()| tool_version("cargo")),
+            hint: "Install Rust with rustup (https://rustup.rs), which puts cargo in ~/.cargo/bin".to_string(),
+            critical: true,
+        },
+        check_config(),
+        check_horizon(),
+        check_github().await,
+    ];
+
+    let mut critical_failures = 0;
+    let mut warnings = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(detail) => outln!("✅ {}: {}", check.name.bold(), detail),
+            Err(e) => {
+                if check.critical {
+                    critical_failures += 1;
+                    outln!("❌ {}: {}", check.name.bold(), e.to_string().red());
+                } else {
+                    warnings += 1;
+                    outln!("⚠️  {}: {}", check.name.bold(), e.to_string().yellow());
+                }
+                outln!("   {}", check.hint.dimmed());
+            }
+        }
+    }
+
+    outln!();
+    if critical_failures > 0 {
+        return Err(anyhow!("{} critical check(s) failed", critical_failures));
+    }
+    if warnings > 0 {
+        outln!("{}", format!("✅ The required tools are available ({} warning(s) above)", warnings).green().bold());
+    } else {
+        outln!("{}", "🎉 Your environment is ready!".green().bold());
+    }
+    Ok(())
+}
+
+/// First line of `<tool> --version`
+fn tool_version(tool: &str) -> Result<String> {
+    let output = Command::new(tool).arg("--version").output()?;
+    if !output.status.success() {
+        return Err(anyhow!("`{} --version` failed", tool));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().trim().to_string())
+}
+
+fn check_config() -> Check {
+    let path = config_path();
+    let result = Config::load().and_then(|_| {
+        let path = path?;
+        Ok(if path.exists() {
+            path.display().to_string()
+        } else {
+            format!("{} (not created yet; defaults are used)", path.display())
+        })
+    });
+    Check {
+        name: "config file",
+        result,
+        hint: "Fix the setting named above, or run `fbcli init` to rewrite the file".to_string(),
+        critical: false,
+    }
+}
+
+/// The Horizon checkout `plugin build` would copy into by default
+fn check_horizon() -> Check {
+    let (path, source) = match std::env::var_os("FBCLI_HORIZON_PATH") {
+        Some(path) => (PathBuf::from(path), "FBCLI_HORIZON_PATH"),
+        None => match Config::load().ok().and_then(|config| config.horizon_path) {
+            Some(path) => (path, "config file"),
+            None => (PathBuf::from("../Horizon"), "default"),
+        },
+    };
+    let result = if path.join("Cargo.toml").is_file() {
+        Ok(format!("{} ({})", path.display(), source))
+    } else {
+        Err(anyhow!("no Horizon checkout at {} ({})", path.display(), source))
+    };
+    Check {
+        name: "Horizon",
+        result,
+        hint: "Only needed to deploy plugins: clone it with `fbcli repo clone Horizon`, or set horizon_path with `fbcli init`".to_string(),
+        critical: false,
+    }
+}
+
+/// Whether the GitHub API answers at all; any HTTP response counts as reachable
+async fn check_github() -> Check {
+    let hint = "Check your network connection or proxy settings (HTTPS_PROXY); cloning and listing repositories need GitHub".to_string();
+    let api_base = match super::repo::api_base() {
+        Ok(api_base) => api_base,
+        Err(e) => return Check { name: "GitHub", result: Err(e), hint, critical: false },
+    };
+    let request = reqwest::Client::new()
+        .get(&api_base)
+        .header("User-Agent", "fbcli")
+        .timeout(NETWORK_TIMEOUT)
+        .send()
+        .await;
+    let result = match request {
+        Ok(response) => Ok(format!("{} reachable ({})", api_base, response.status())),
+        Err(e) if e.is_timeout() => Err(anyhow!("{} did not answer within {}s", api_base, NETWORK_TIMEOUT.as_secs())),
+        Err(e) => Err(anyhow!("cannot reach {}: {}", api_base, e)),
+    };
+    Check { name: "GitHub", result, hint, critical: false }
+}
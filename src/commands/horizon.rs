@@ -1,13 +1,29 @@
-use clap::Subcommand;
+use clap::builder::FalseyValueParser;
+use clap::{Args, Subcommand, ValueEnum};
 use anyhow::{anyhow, Context, Result};
 use colored::*;
-use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
+use chrono::{DateTime, Local};
+use console::{style, Term};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, IsTerminal};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
-use toml_edit::{Document, value};
+use toml_edit::{Document, Item, value};
+
+use crate::commands::repo::CredentialCache;
+use crate::config::{config_path, Config};
+use crate::ui;
+use crate::utils::{
+    cache_dir, check_cargo_available, command_exists, current_dir, dir_size, format_bytes, plugin_name_problems,
+    render_table, sha256_file, validate_plugin_name,
+};
 
 #[derive(Subcommand)]
 pub enum HorizonCommand {
@@ -19,30 +35,278 @@ pub enum HorizonCommand {
 #[derive(Subcommand)]
 pub enum PluginCommand {
     /// Create a new Horizon plugin from template
-    New {
-        /// Name of the plugin
-        name: String,
-        /// Target directory (defaults to current directory)
-        #[arg(short, long)]
-        path: Option<PathBuf>,
-    },
+    New(NewArgs),
     /// Build a plugin (from plugin dir or Horizon repo root)
-    Build {
-        /// Plugin name (positional, required if in Horizon repo root)
-        #[arg()]
-        plugin: Option<String>,
-        /// Horizon project path (defaults to ../Horizon)
-        #[arg(long)]
-        horizon_path: Option<PathBuf>,
-        /// Skip copying to Horizon plugins directory
-        #[arg(long)]
-        no_copy: bool,
-        /// Plugin name (optional, for --plugin usage)
-        #[arg(long)]
-        plugin_flag: Option<String>,
+    Build(BuildArgs),
+    /// List the plugin crates in a Horizon repo and whether they are built
+    List(ListArgs),
+    /// List the templates `plugin new --template` accepts (works offline)
+    Templates,
+    /// Check a plugin crate for common misconfigurations
+    Doctor(DoctorArgs),
+    /// Generate an integration test harness for a plugin crate
+    ScaffoldTest(ScaffoldTestArgs),
+    /// Run `cargo test` for a plugin (from plugin dir or Horizon repo root)
+    Test(TestArgs),
+    /// Load a plugin library and instantiate it (child process of `build --runtime-check`)
+    #[command(name = "__runtime-check", hide = true)]
+    RuntimeCheckHost {
+        library: PathBuf,
     },
 }
 
+#[derive(Args, Debug)]
+pub struct NewArgs {
+    /// Name of the plugin
+    name: String,
+    /// Target directory (defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+    /// Name of the created directory, if it should differ from the plugin name
+    #[arg(long, visible_alias = "target-dir-name", value_name = "NAME", value_parser = parse_dir_name)]
+    dir: Option<String>,
+    /// Version control to initialize in the new plugin
+    #[arg(long, value_enum, default_value_t = Vcs::Git)]
+    vcs: Vcs,
+    /// Keep the template's README.md, filling in {{plugin_name}}-style placeholders
+    #[arg(long)]
+    keep_readme: bool,
+    /// Async runtime setup (default: keep the template's dependencies as-is)
+    #[arg(long, value_enum)]
+    async_runtime: Option<AsyncRuntime>,
+    /// Template name (see `plugin templates`) or git URL (defaults to template_url in the config file, then Horizon-Plugin-Sample)
+    #[arg(long, value_name = "NAME|URL")]
+    template: Option<String>,
+    /// Branch of the sample repository to use (defaults to its default branch)
+    #[arg(long, value_name = "BRANCH")]
+    template_branch: Option<String>,
+    /// Directory for the cached template mirror (defaults to the user cache dir)
+    #[arg(long, value_name = "DIR")]
+    mirror_to: Option<PathBuf>,
+    /// Use the cached template without contacting GitHub
+    #[arg(long, conflicts_with = "refresh")]
+    cached: bool,
+    /// Generate the plugin from the built-in template instead of cloning the sample repository
+    #[arg(long, conflicts_with_all = ["template", "cached", "refresh", "template_branch", "mirror_to"])]
+    offline: bool,
+    /// Update the cached template even if it was fetched recently
+    #[arg(long)]
+    refresh: bool,
+    /// Set a Cargo.toml key, e.g. package.authors='["Ada"]' (repeatable; values are TOML)
+    #[arg(long = "set", alias = "overwrite-cargo-keys", value_name = "KEY=VALUE", value_parser = parse_cargo_override)]
+    cargo_overrides: Vec<(String, String)>,
+    /// Add a dependency, e.g. rand or reqwest=0.11 (repeatable; no version means the latest on crates.io)
+    #[arg(long = "dependency", visible_alias = "dep", value_name = "NAME[=VERSION]", value_parser = parse_dependency_arg)]
+    dependencies: Vec<(String, Option<String>)>,
+    /// Accept a name that breaks the naming conventions, warning instead of failing
+    #[arg(long)]
+    force_name: bool,
+    /// Name of the generated plugin struct (defaults to the plugin name in PascalCase plus "Plugin")
+    #[arg(long = "name", value_name = "STRUCT_NAME", value_parser = parse_struct_name)]
+    struct_name: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct BuildArgs {
+    /// Plugin name (positional, required if in Horizon repo root)
+    #[arg()]
+    plugin: Option<String>,
+    /// Horizon project path (defaults to horizon_path in the config file, then ../Horizon)
+    #[arg(long, env = "FBCLI_HORIZON_PATH")]
+    horizon_path: Option<PathBuf>,
+    /// Skip copying to Horizon plugins directory
+    #[arg(long)]
+    no_copy: bool,
+    /// Fail instead of falling back to ../Horizon when no Horizon path is given
+    #[arg(long, env = "FBCLI_REQUIRE_EXPLICIT_HORIZON_PATH", value_parser = FalseyValueParser::new())]
+    no_default_horizon_path: bool,
+    /// Plugin name (optional, for --plugin usage)
+    #[arg(long)]
+    plugin_flag: Option<String>,
+    /// Build exactly the crate with this Cargo.toml, wherever it is and whatever it's called
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["plugin", "plugin_flag", "workspace_root"])]
+    manifest_path: Option<PathBuf>,
+    /// Retry the copy for up to this many seconds if the destination is locked
+    #[arg(long, value_name = "SECS")]
+    wait_for_lock: Option<u64>,
+    /// Also copy debug symbols (.pdb, .dSYM, .dwp) found next to the library
+    #[arg(long)]
+    copy_symbols: bool,
+    /// Write a JSON manifest (name, version, SHA-256, target, build time) next to the copied library
+    #[arg(long, conflicts_with = "no_copy")]
+    copy_manifest: bool,
+    /// Name the copied library from plugin_id/display_name in [package.metadata.horizon]
+    #[arg(long)]
+    name_from_metadata: bool,
+    /// Enable the cargo features listed in `features` under [package.metadata.horizon]
+    #[arg(long, conflicts_with = "copy_only")]
+    features_from_metadata: bool,
+    /// Run `cargo clean -p <package>` first so a stale library can never be picked up
+    #[arg(long, visible_alias = "clean")]
+    clean_first: bool,
+    /// Build with the dev profile (target/debug) instead of --release
+    #[arg(long)]
+    debug: bool,
+    /// Show a desktop notification when the build finishes
+    #[arg(long)]
+    notify: bool,
+    /// Cross-compile for this target triple, e.g. x86_64-pc-windows-gnu
+    #[arg(long = "target", value_name = "TRIPLE")]
+    target_triple: Option<String>,
+    /// Workspace root whose target dir holds the built library (defaults to the nearest one)
+    #[arg(long, value_name = "PATH")]
+    workspace_root: Option<PathBuf>,
+    /// Print only the absolute path of the built library, for scripts
+    #[arg(long)]
+    print_artifact_path: bool,
+    /// Load the built library in a separate process and instantiate the plugin before deploying it
+    #[arg(long)]
+    runtime_check: bool,
+    /// Strip symbols from a copy of the built library with the platform `strip` tool and deploy that
+    #[arg(long)]
+    strip: bool,
+    /// After copying, signal this process (e.g. a running Horizon server) to reload its plugins
+    #[arg(long, visible_alias = "reload-pid", value_name = "PID", conflicts_with = "no_copy")]
+    signal_pid: Option<u32>,
+    /// Signal sent to --signal-pid, by name or number (Unix only)
+    #[arg(long, value_name = "SIGNAL", default_value = "HUP", requires = "signal_pid", value_parser = parse_signal)]
+    signal: String,
+    /// Skip building and deploy the library from a previous build
+    #[arg(long, conflicts_with_all = ["no_copy", "clean_first"])]
+    copy_only: bool,
+    /// Keep running and rebuild whenever the plugin's sources change
+    #[arg(long, conflicts_with_all = ["print_artifact_path", "copy_only"])]
+    watch: bool,
+    /// Clear the terminal before each rebuild in watch mode (ignored when not a terminal)
+    #[arg(long, requires = "watch")]
+    clear: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Horizon repo root (defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Plugin crate directory (defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+    /// Horizon project path, used to pick the horizon_event_system version (defaults to horizon_path in the config file, then ../Horizon)
+    #[arg(long)]
+    horizon_path: Option<PathBuf>,
+    /// Automatically repair the issues that can be fixed
+    #[arg(long)]
+    fix: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TestArgs {
+    /// Plugin name (required if in Horizon repo root)
+    plugin: Option<String>,
+    /// Arguments passed on to `cargo test`, after `--`
+    #[arg(last = true, value_name = "CARGO_TEST_ARGS")]
+    cargo_args: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ScaffoldTestArgs {
+    /// Plugin crate directory (defaults to current directory)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+    /// Overwrite an existing tests/plugin_lifecycle.rs
+    #[arg(long)]
+    force: bool,
+}
+
+/// Version control setup for newly created plugins
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vcs {
+    /// Initialize a fresh git repository with an initial commit
+    Git,
+    /// Leave the plugin directory untracked
+    None,
+}
+
+/// Cargo profile a plugin is built with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BuildProfile {
+    Release,
+    Debug,
+}
+
+impl BuildProfile {
+    /// Directory under `target/` holding the profile's artifacts
+    fn dir_name(self) -> &'static str {
+        match self {
+            BuildProfile::Release => "release",
+            BuildProfile::Debug => "debug",
+        }
+    }
+
+    /// Arguments that select the profile for `cargo build` and `cargo clean`
+    fn cargo_args(self) -> &'static [&'static str] {
+        match self {
+            BuildProfile::Release => &["--release"],
+            BuildProfile::Debug => &[],
+        }
+    }
+}
+
+/// The target triple fbcli itself was built for, which cargo builds for by default
+const HOST_TARGET: &str = env!("FBCLI_TARGET");
+
+impl BuildArgs {
+    fn profile(&self) -> BuildProfile {
+        if self.debug { BuildProfile::Debug } else { BuildProfile::Release }
+    }
+
+    /// Target triple the library is built for
+    fn target_triple(&self) -> &str {
+        self.target_triple.as_deref().unwrap_or(HOST_TARGET)
+    }
+
+    /// Arguments that select the profile and target for `cargo build` and `cargo clean`
+    fn cargo_build_args(&self) -> Vec<&str> {
+        let mut cargo_args = self.profile().cargo_args().to_vec();
+        if let Some(triple) = &self.target_triple {
+            cargo_args.extend(["--target", triple]);
+        }
+        cargo_args
+    }
+
+    /// Directory under `target/` holding the artifacts: `release`, or
+    /// `<triple>/release` when cross-compiling with `--target`
+    fn artifact_dir(&self) -> PathBuf {
+        match &self.target_triple {
+            Some(triple) => Path::new(triple).join(self.profile().dir_name()),
+            None => PathBuf::from(self.profile().dir_name()),
+        }
+    }
+}
+
+/// Async runtime setup for newly created plugins
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsyncRuntime {
+    /// Add tokio as a dev-dependency and generate a #[tokio::test] scaffold
+    Tokio,
+    /// Remove the tokio dev-dependency; the server provides the runtime
+    None,
+}
+
+impl HorizonCommand {
+    /// Whether the command writes machine-readable output that decoration would corrupt
+    pub fn is_machine_readable(&self) -> bool {
+        match self {
+            HorizonCommand::Plugin(PluginCommand::Build(args)) => args.print_artifact_path,
+            HorizonCommand::Plugin(PluginCommand::RuntimeCheckHost { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
 pub async fn handle_command(cmd: HorizonCommand) -> Result<()> {
     match cmd {
         HorizonCommand::Plugin(plugin_cmd) => handle_plugin_command(plugin_cmd).await,
@@ -51,470 +315,3392 @@ pub async fn handle_command(cmd: HorizonCommand) -> Result<()> {
 
 async fn handle_plugin_command(cmd: PluginCommand) -> Result<()> {
     match cmd {
-        PluginCommand::New { name, path } => create_new_plugin(&name, path).await,
-        PluginCommand::Build { plugin, horizon_path, no_copy, plugin_flag } => {
-            // Prefer positional plugin argument, fallback to --plugin
-            let plugin_name = plugin.or(plugin_flag);
-            build_plugin(horizon_path, no_copy, plugin_name).await
+        PluginCommand::New(args) => create_new_plugin(args).await,
+        PluginCommand::Build(mut args) => {
+            args.horizon_path = configured_horizon_path(args.horizon_path)?;
+            if args.watch { watch_plugin(&args) } else { build_plugin(&args) }
         }
+        PluginCommand::List(args) => list_plugins(args),
+        PluginCommand::Templates => list_templates(),
+        PluginCommand::Doctor(mut args) => {
+            args.horizon_path = configured_horizon_path(args.horizon_path)?;
+            plugin_doctor(args)
+        }
+        PluginCommand::ScaffoldTest(args) => scaffold_plugin_test(args),
+        PluginCommand::Test(args) => test_plugin(args),
+        PluginCommand::RuntimeCheckHost { library } => runtime_check_host(&library),
+    }
+}
+
+/// The Horizon path given on the command line or in FBCLI_HORIZON_PATH, else the config file's `horizon_path`
+fn configured_horizon_path(arg: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    match arg {
+        Some(path) => Ok(Some(path)),
+        None => Ok(Config::load()?.horizon_path),
     }
 }
 
-async fn create_new_plugin(name: &str, target_path: Option<PathBuf>) -> Result<()> {
-    let target_dir = target_path.unwrap_or_else(|| PathBuf::from("."));
-    let plugin_dir = target_dir.join(name);
+async fn create_new_plugin(args: NewArgs) -> Result<()> {
+    let name = args.name.as_str();
+    if args.force_name {
+        let problems = plugin_name_problems(name)?;
+        for problem in &problems {
+            outln!("⚠️  {} (continuing because of --force-name)", problem);
+        }
+        if !problems.is_empty() {
+            outln!("⚠️  `plugin build` may not detect this plugin; pass its package name explicitly if it doesn't");
+        }
+    } else {
+        validate_plugin_name(name)?;
+    }
+    let vcs = args.vcs;
+    let target_dir = args.path.clone().unwrap_or_else(|| PathBuf::from("."));
+    let dir_name = args.dir.as_deref().unwrap_or(name);
+    let plugin_dir = target_dir.join(dir_name);
 
-    println!("🔧 Creating new Horizon plugin: {}", style(name).cyan().bold());
-    println!("📂 Target directory: {}", style(plugin_dir.display()).yellow());
+    if plugin_dir.exists() {
+        return Err(anyhow!(
+            "'{}' already exists; choose another plugin name or --path, or remove the directory",
+            plugin_dir.display()
+        ));
+    }
+    check_workspace_collision(&target_dir, name)?;
+    let dependencies = resolve_dependency_versions(&args.dependencies, args.offline).await?;
+
+    outln!("🔧 Creating new Horizon plugin: {}", style(name).cyan().bold());
+    statusln!("📂 Target directory: {}", style(plugin_dir.display()).yellow());
+
+    let template = if args.offline {
+        Template::Embedded
+    } else {
+        let config = load_config_for_template(&args)?;
+        let name = args.template.clone()
+            .or_else(|| config.template_url.clone())
+            .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string());
+        resolve_template(&name, config)?
+    };
+    let embedded = template == Template::Embedded;
 
     // Create progress bar
-    let pb = ProgressBar::new(4);
+    let steps = if embedded { 3 } else { 4 };
+    let pb = ProgressBar::new(if vcs == Vcs::Git { steps + 1 } else { steps });
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
             .unwrap()
             .progress_chars("##-"),
     );
+    if !ui::shows_progress() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    let guard = PartialPluginGuard::new(&plugin_dir, &pb);
 
     // Step 1: Clone the sample repository
-    pb.set_message("Cloning Horizon-Plugin-Sample...");
-    clone_sample_repo(&plugin_dir).await?;
-    pb.inc(1);
+    if let Template::Git(url) = &template {
+        let url = url.as_str();
+        pb.set_message(format!("Cloning {}...", template_repo_name(url)));
+        let template = TemplateSource {
+            url: url.to_string(),
+            mirror: match &args.mirror_to {
+                Some(dir) => dir.clone(),
+                None => cache_dir()?.join("templates").join(template_mirror_name(url)),
+            },
+            offline: args.cached,
+            refresh: args.refresh,
+        };
+        clone_sample_repo(&plugin_dir, args.template_branch.as_deref(), &template, guard.interrupted()).await?;
+        guard.exit_if_interrupted();
+        pb.inc(1);
+    } else {
+        write_embedded_template(&plugin_dir)?;
+    }
 
     // Step 2: Update Cargo.toml with new name
     pb.set_message("Updating Cargo.toml...");
-    update_cargo_toml(&plugin_dir, name)?;
+    update_cargo_toml(&plugin_dir, name, &dependencies, &args.cargo_overrides)?;
+    if let Some(runtime) = args.async_runtime {
+        apply_async_runtime(&plugin_dir, runtime)?;
+    }
     pb.inc(1);
 
     // Step 3: Update plugin code
     pb.set_message("Updating plugin code...");
-    update_plugin_code(&plugin_dir, name)?;
+    let struct_name = args.struct_name.clone().unwrap_or_else(|| default_struct_name(name));
+    update_plugin_code(&plugin_dir, name, &struct_name, args.async_runtime == Some(AsyncRuntime::Tokio))?;
     pb.inc(1);
 
     // Step 4: Clean up
     pb.set_message("Cleaning up...");
-    cleanup_plugin_directory(&plugin_dir)?;
+    cleanup_plugin_directory(&plugin_dir, args.keep_readme)?;
+    if args.keep_readme {
+        fill_readme_placeholders(&plugin_dir, name, &struct_name)?;
+    }
     pb.inc(1);
 
-    pb.finish_with_message("✅ Plugin created successfully!");
-    
-    println!();
-    println!("{}", "🎉 Plugin created successfully!".green().bold());
-    println!("📁 Plugin location: {}", style(plugin_dir.display()).yellow());
-    println!();
-    println!("{}", "Next steps:".bold());
-    println!("  1. cd {}", name);
-    println!("  2. fbcli horizon plugin build");
-    println!();
-
-    Ok(())
-}
-
-async fn clone_sample_repo(target_dir: &Path) -> Result<()> {
-    use git2::Repository;
+    // Step 5: Initialize version control
+    if vcs == Vcs::Git {
+        pb.set_message("Initializing git repository...");
+        init_plugin_repository(&plugin_dir)?;
+        pb.inc(1);
+    }
 
-    let repo_url = "https://github.com/Far-Beyond-Dev/Horizon-Plugin-Sample.git";
-    
-    // Clone the repository
-    Repository::clone(repo_url, target_dir)
-        .with_context(|| format!("Failed to clone sample repository to {}", target_dir.display()))?;
+    guard.disarm();
+    pb.finish_with_message(ui::render("✅ Plugin created successfully!"));
     
-    Ok(())
-}
+    outln!();
+    outln!("{}", "🎉 Plugin created successfully!".green().bold());
+    outln!("📁 Plugin location: {}", style(plugin_dir.display()).yellow());
+    outln!();
+    outln!("{}", "Next steps:".bold());
+    outln!("  1. cd {}", dir_name);
+    if args.dir.is_some() && !dir_name.starts_with("plugin_") {
+        // Directory-name detection in `plugin build` needs the plugin_ prefix
+        outln!("  2. fbcli horizon plugin build --manifest-path Cargo.toml");
+        outln!("  {} `plugin build` only finds plugin directories named plugin_*, so point it at the manifest", style("ℹ").blue());
+    } else {
+        outln!("  2. fbcli horizon plugin build");
+    }
+    match vcs {
+        Vcs::Git => outln!("  {} Git repository initialized with an initial commit", style("ℹ").blue()),
+        Vcs::None => outln!("  {} No git repository created; run `git init` if you want one", style("ℹ").blue()),
+    }
+    outln!();
 
-fn update_cargo_toml(plugin_dir: &Path, plugin_name: &str) -> Result<()> {
-    let cargo_path = plugin_dir.join("Cargo.toml");
-    let content = fs::read_to_string(&cargo_path)?;
-    
-    let mut doc = content.parse::<Document>()?;
-    
-    // Update package name
-    doc["package"]["name"] = value(&format!("plugin_{}", plugin_name));
-    
-    fs::write(cargo_path, doc.to_string())?;
     Ok(())
 }
 
-fn update_plugin_code(plugin_dir: &Path, plugin_name: &str) -> Result<()> {
-    let lib_path = plugin_dir.join("src/lib.rs");
-    
-    // Create a basic version of the greeter plugin with the new name
-    let new_content = create_basic_plugin_template(plugin_name);
-    
-    fs::write(lib_path, new_content)?;
-    Ok(())
+/// Parse `--dir`: a single directory name, not a path
+fn parse_dir_name(value: &str) -> Result<String, String> {
+    let mut components = Path::new(value).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(_)), None) => Ok(value.to_string()),
+        _ => Err("expected a single directory name; use --path to choose where it is created".to_string()),
+    }
 }
 
-fn create_basic_plugin_template(plugin_name: &str) -> String {
-    let struct_name = to_pascal_case(plugin_name);
-    
-    format!(r#"use async_trait::async_trait;
-use horizon_event_system::{{
-    create_simple_plugin, current_timestamp, EventSystem, LogLevel,
-    PluginError, ServerContext, SimplePlugin,
-}};
-use serde::{{Deserialize, Serialize}};
-use std::sync::Arc;
-use tracing::{{error, info, warn}};
+/// Rust keywords, which can't name the generated plugin struct
+const RUST_KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "static", "struct", "super",
+    "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
 
-/// {} Plugin
-pub struct {}Plugin {{
-    name: String,
-}}
+/// Parse `--name`: the plugin struct's name, which has to be a Rust identifier
+fn parse_struct_name(value: &str) -> Result<String, String> {
+    let mut chars = value.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && value != "_";
+    if !is_identifier {
+        return Err("expected a Rust identifier (letters, digits and _, not starting with a digit), e.g. HTTPServerPlugin".to_string());
+    }
+    if RUST_KEYWORDS.contains(&value) {
+        return Err(format!("'{}' is a Rust keyword", value));
+    }
+    Ok(value.to_string())
+}
 
-impl {}Plugin {{
-    pub fn new() -> Self {{
-        info!("🔧 {}Plugin: Creating new instance");
-        Self {{
-            name: "{}".to_string(),
-        }}
-    }}
-}}
+/// Refuse to scaffold into a workspace `crates/` dir that already has this plugin
+fn check_workspace_collision(target_dir: &Path, name: &str) -> Result<()> {
+    let is_crates_dir = target_dir.canonicalize()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|n| n == "crates"))
+        .unwrap_or(false);
+    if !is_crates_dir {
+        return Ok(());
+    }
 
-#[async_trait]
-impl SimplePlugin for {}Plugin {{
-    fn name(&self) -> &str {{
-        &self.name
-    }}
+    let workspace_toml = target_dir.join("../Cargo.toml");
+    let content = match fs::read_to_string(&workspace_toml) {
+        Ok(content) => content,
+        Err(_) => return Ok(()),
+    };
+    let doc = content.parse::<Document>()?;
+    let Some(workspace) = doc.get("workspace") else {
+        return Ok(());
+    };
 
-    fn version(&self) -> &str {{
-        "1.0.0"
-    }}
+    let prefixed = format!("plugin_{}", name.trim_start_matches("plugin_"));
+    let candidates = [name, prefixed.as_str()];
 
-    async fn register_handlers(&mut self, _events: Arc<EventSystem>) -> Result<(), PluginError> {{
-        info!("🔧 {}Plugin: Registering event handlers...");
-        
-        // TODO: Register your event handlers here
-        // Example:
-        // register_handlers!(events; core {{
-        //     "your_event" => |event: serde_json::Value| {{
-        //         info!("Received event: {{:?}}", event);
-        //         Ok(())
-        //     }}
-        // }})?;
-        
-        info!("🔧 {}Plugin: ✅ All handlers registered successfully!");
-        Ok(())
-    }}
+    let members = workspace.get("members").and_then(|m| m.as_array());
+    let member_clash = members
+        .into_iter()
+        .flat_map(|members| members.iter())
+        .filter_map(|member| member.as_str())
+        .filter_map(|member| Path::new(member).file_name().and_then(|n| n.to_str()))
+        .find(|member| candidates.contains(member));
+    let dir_clash = candidates.iter().find(|candidate| target_dir.join(candidate).exists());
 
-    async fn on_init(&mut self, context: Arc<dyn ServerContext>) -> Result<(), PluginError> {{
-        context.log(
-            LogLevel::Info,
-            "🔧 {}Plugin: Starting up!",
-        );
+    if let Some(existing) = member_clash.or(dir_clash.copied()) {
+        return Err(anyhow!(
+            "A crate named '{}' already exists in this workspace ({}). Choose a different plugin name.",
+            existing,
+            target_dir.join(existing).display()
+        ));
+    }
 
-        // TODO: Add your initialization logic here
-        
-        info!("🔧 {}Plugin: ✅ Initialization complete!");
-        Ok(())
-    }}
+    Ok(())
+}
 
-    async fn on_shutdown(&mut self, context: Arc<dyn ServerContext>) -> Result<(), PluginError> {{
-        context.log(
-            LogLevel::Info,
-            "🔧 {}Plugin: Shutting down!",
-        );
+const SAMPLE_REPO_URL: &str = "https://github.com/Far-Beyond-Dev/Horizon-Plugin-Sample.git";
 
-        // TODO: Add your cleanup logic here
+/// Template `plugin new` uses when no --template is given
+const DEFAULT_TEMPLATE: &str = "sample";
 
-        info!("🔧 {}Plugin: ✅ Shutdown complete!");
-        Ok(())
-    }}
-}}
+/// Templates that ship with fbcli, with the description `plugin templates` shows
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("sample", "The official Horizon-Plugin-Sample repository (default)"),
+    ("builtin", "Minimal plugin embedded in fbcli; never needs the network (same as --offline)"),
+];
 
-// Create the plugin using the macro
-create_simple_plugin!({}Plugin);
-"#, 
-        &struct_name,      // Comment
-        &struct_name,      // struct name
-        &struct_name,      // impl block
-        &struct_name,      // log message
-        plugin_name,       // name field
-        &struct_name,      // trait impl
-        &struct_name,      // register_handlers log
-        &struct_name,      // register_handlers success log
-        &struct_name,      // on_init log
-        &struct_name,      // on_init success log
-        &struct_name,      // on_shutdown log
-        &struct_name,      // on_shutdown success log
-        &struct_name       // macro call
-    )
+/// Where a new plugin's files come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Template {
+    /// Cloned from a git repository through the template cache
+    Git(String),
+    /// Written from the template embedded in fbcli
+    Embedded,
 }
 
-fn to_pascal_case(s: &str) -> String {
-    s.split(|c: char| !c.is_alphanumeric())
-        .filter(|s| !s.is_empty())
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
-            }
-        })
-        .collect()
+/// The config file is only needed for the default template (`template_url`)
+/// and to resolve names that aren't built in
+fn load_config_for_template(args: &NewArgs) -> Result<Config> {
+    match args.template.as_deref() {
+        Some(name) if BUILTIN_TEMPLATES.iter().any(|(builtin, _)| *builtin == name) => Ok(Config::default()),
+        _ => Config::load(),
+    }
 }
 
-fn cleanup_plugin_directory(plugin_dir: &Path) -> Result<()> {
-    // Remove .git directory
-    let git_dir = plugin_dir.join(".git");
-    if git_dir.exists() {
-        fs::remove_dir_all(git_dir)?;
+/// Resolve a --template argument: a built-in name, a name from the config
+/// file's `[templates]` table, or a git URL
+fn resolve_template(arg: &str, config: Config) -> Result<Template> {
+    match arg {
+        "sample" => return Ok(Template::Git(SAMPLE_REPO_URL.to_string())),
+        "builtin" => return Ok(Template::Embedded),
+        _ => {}
     }
-    
-    // Remove README if it exists (they can create their own)
-    let readme_path = plugin_dir.join("README.md");
-    if readme_path.exists() {
-        fs::remove_file(readme_path)?;
+    if let Some(template) = config.templates.get(arg) {
+        return Ok(Template::Git(template.url.clone()));
     }
-    
-    Ok(())
+    if !looks_like_git_remote(arg) {
+        return Err(anyhow!(
+            "'{}' is neither a known template nor a git repository URL; run `fbcli horizon plugin templates` \
+             to see the available names, or use https://host/owner/repo.git or git@host:owner/repo.git",
+            arg
+        ));
+    }
+    Ok(Template::Git(arg.to_string()))
 }
 
-async fn build_plugin(horizon_path: Option<PathBuf>, no_copy: bool, plugin: Option<String>) -> Result<()> {
-    println!("🔨 Building Horizon plugin...");
-
-    // Determine if we're in Horizon repo root or plugin crate dir
-    let current_dir = std::env::current_dir()?;
-    let cargo_toml = current_dir.join("Cargo.toml");
-    let crates_dir = current_dir.join("crates");
-    let in_plugin_dir = cargo_toml.exists();
-    let in_horizon_root = crates_dir.exists();
+fn list_templates() -> Result<()> {
+    let config = Config::load()?;
+    let cached = |url: &str| -> Result<bool> {
+        Ok(cache_dir()?.join("templates").join(template_mirror_name(url)).exists())
+    };
 
-    let (plugin_dir, package_name) = {
-        // Use directory name for plugin detection, but use package name for DLL search
-        let dir_name = current_dir.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if dir_name == "plugin_system" {
-            return Err(anyhow!("plugin_system is not a buildable plugin crate"));
-        }
-        if in_plugin_dir && dir_name.starts_with("plugin_") {
-            println!("[DEBUG] Detected plugin crate by directory name: {}", dir_name);
-            let cargo_toml_path = current_dir.join("Cargo.toml");
-            let content = fs::read_to_string(&cargo_toml_path)?;
-            let doc = content.parse::<Document>()?;
-            let pkg_table = doc.get("package").and_then(|t| t.as_table());
-            let pkg_name = pkg_table.and_then(|t| t.get("name")).and_then(|n| n.as_str());
-            let pkg_name = match pkg_name {
-                Some(name) => name.to_string(),
-                None => {
-                    return Err(anyhow!("Cargo.toml missing [package] name field ({}).", cargo_toml_path.display()));
-                }
-            };
-            (current_dir.clone(), pkg_name)
-        } else if in_horizon_root {
-            let plugin_arg = plugin.ok_or_else(|| anyhow!("--plugin argument required when in Horizon repo root"))?;
-            let mut crate_name = plugin_arg.clone();
-            if !crate_name.starts_with("plugin_") {
-                crate_name = format!("plugin_{}", crate_name);
-            }
-            if crate_name == "plugin_system" {
-                return Err(anyhow!("plugin_system is not a buildable plugin crate"));
-            }
-            let plugin_path = crates_dir.join(&crate_name);
-            if !plugin_path.exists() {
-                return Err(anyhow!("Plugin crate '{}' not found in crates dir", crate_name));
-            }
-            println!("[DEBUG] Detected plugin crate by directory name: {}", crate_name);
-            let cargo_toml_path = plugin_path.join("Cargo.toml");
-            let content = fs::read_to_string(&cargo_toml_path)?;
-            let doc = content.parse::<Document>()?;
-            let pkg_table = doc.get("package").and_then(|t| t.as_table());
-            let pkg_name = pkg_table.and_then(|t| t.get("name")).and_then(|n| n.as_str());
-            let pkg_name = match pkg_name {
-                Some(name) => name.to_string(),
-                None => {
-                    return Err(anyhow!("Cargo.toml missing [package] name field ({}).", cargo_toml_path.display()));
-                }
-            };
-            (plugin_path, pkg_name)
-        } else {
-            return Err(anyhow!("Not in a plugin crate directory or Horizon repo root"));
+    let mut rows = Vec::new();
+    for (name, description) in BUILTIN_TEMPLATES {
+        let (source, offline) = match resolve_template(name, Config::default())? {
+            Template::Git(url) => (url.clone(), if cached(&url)? { "cached" } else { "no" }),
+            Template::Embedded => ("embedded".to_string(), "yes"),
+        };
+        rows.push(vec![name.to_string(), source, offline.to_string(), description.to_string()]);
+    }
+    for (name, template) in &config.templates {
+        if BUILTIN_TEMPLATES.iter().any(|(builtin, _)| builtin == name) {
+            outln!("⚠️  Ignoring [templates.{}] in the config file: it shadows a built-in template", name);
+            continue;
         }
-    };
+        let offline = if cached(&template.url)? { "cached" } else { "no" };
+        let description = template.description.clone().unwrap_or_default();
+        rows.push(vec![name.clone(), template.url.clone(), offline.to_string(), description]);
+    }
 
-    // Create progress bar
-    let pb = ProgressBar::new(if no_copy { 2 } else { 3 });
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
-            .unwrap()
-            .progress_chars("##-"),
-    );
+    outln!("📋 Available plugin templates:");
+    outln!();
+    outln!("{}", render_table(&["Template", "Source", "Offline", "Description"], &rows));
+    outln!();
+    outln!("Use one with: fbcli horizon plugin new <name> --template <template>");
+    outln!("Add your own under [templates] in {}", config_path()?.display());
+    Ok(())
+}
 
-    // Step 1: Build the plugin
-    pb.set_message("Building plugin (release mode)...");
-    build_release_in_dir(&plugin_dir)?;
-    pb.inc(1);
+/// Deletes a half-created plugin directory when `plugin new` fails or is
+/// interrupted with Ctrl-C, so the command can simply be run again.
+///
+/// Ctrl-C only raises the `interrupted` flag: the clone polls it and stops,
+/// and the directory is removed once nothing is writing into it anymore.
+/// A second Ctrl-C exits right away, leaving the directory behind.
+struct PartialPluginGuard {
+    dir: PathBuf,
+    pb: ProgressBar,
+    interrupted: Arc<AtomicBool>,
+    interrupt: tokio::task::JoinHandle<()>,
+    disarmed: bool,
+}
 
-    // Step 2: Find the built library
-    pb.set_message("Locating built library...");
-    let lib_path = if in_horizon_root {
-        // Built library is in workspace root target/release
-        let workspace_target_dir = current_dir.join("target/release");
-        find_built_library_in_workspace(&workspace_target_dir, &package_name)?
-    } else {
-        // Check for workspace root in parent directories
-        let mut ancestor = plugin_dir.as_path();
-        let mut workspace_root = None;
-        while let Some(parent) = ancestor.parent() {
-            let candidate = parent.join("Cargo.toml");
-            if candidate.exists() {
-                let content = fs::read_to_string(&candidate)?;
-                if content.contains("[workspace]") {
-                    workspace_root = Some(parent.to_path_buf());
-                    break;
+impl PartialPluginGuard {
+    fn new(dir: &Path, pb: &ProgressBar) -> Self {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let interrupt = {
+            let interrupted = interrupted.clone();
+            let pb = pb.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
                 }
-            }
-            ancestor = parent;
-        }
-        let target_dir = if let Some(root) = workspace_root {
-            println!("[DEBUG] Found workspace root: {}", root.display());
-            root.join("target/release")
-        } else {
-            plugin_dir.join("target/release")
+                interrupted.store(true, Ordering::SeqCst);
+                pb.set_message("Interrupted; stopping (press Ctrl-C again to quit now)...");
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    pb.finish_and_clear();
+                    let _ = Term::stderr().show_cursor();
+                    std::process::exit(130);
+                }
+            })
         };
-        find_built_library_in_workspace(&target_dir, &package_name)?
-    };
-    pb.inc(1);
+        Self { dir: dir.to_path_buf(), pb: pb.clone(), interrupted, interrupt, disarmed: false }
+    }
 
-    // Step 3: Copy to Horizon plugins directory (if not skipped)
-    if !no_copy {
-        pb.set_message("Copying to Horizon plugins directory...");
-        let target_path = horizon_path.clone().unwrap_or_else(|| PathBuf::from("../Horizon"));
-        copy_to_horizon_plugins(&lib_path, &target_path)?;
-        pb.inc(1);
+    /// Flag raised by Ctrl-C, for long-running steps to poll
+    fn interrupted(&self) -> Arc<AtomicBool> {
+        self.interrupted.clone()
     }
 
-    pb.finish_with_message("✅ Plugin built successfully!");
+    /// Between steps: after Ctrl-C, remove the directory and exit
+    fn exit_if_interrupted(&self) {
+        if !self.interrupted.load(Ordering::SeqCst) {
+            return;
+        }
+        self.pb.finish_and_clear();
+        let _ = Term::stderr().show_cursor();
+        remove_partial_plugin(&self.dir);
+        outln!("{} Interrupted; removed the partially created {}", "⚠️".yellow(), self.dir.display());
+        std::process::exit(130);
+    }
 
-    println!();
-    println!("{}", "🎉 Plugin built successfully!".green().bold());
-    println!("📄 Library: {}", style(lib_path.display()).yellow());
+    /// Keep the directory: the plugin was created successfully
+    fn disarm(mut self) {
+        self.exit_if_interrupted();
+        self.disarmed = true;
+    }
+}
 
-    if !no_copy {
-        let target_path = horizon_path.unwrap_or_else(|| PathBuf::from("../Horizon"));
-        let plugins_dir = target_path.join("plugins");
-        println!("📁 Copied to: {}", style(plugins_dir.display()).yellow());
+impl Drop for PartialPluginGuard {
+    fn drop(&mut self) {
+        self.interrupt.abort();
+        if !self.disarmed {
+            // A step that failed because of Ctrl-C exits with 130 instead of its error
+            self.exit_if_interrupted();
+            remove_partial_plugin(&self.dir);
+        }
     }
+}
 
-    println!();
-    Ok(())
+fn remove_partial_plugin(dir: &Path) {
+    // Files can briefly stay locked (e.g. by a virus scanner on Windows), so retry a few times
+    for attempt in 0..3 {
+        if attempt > 0 {
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        if !dir.exists() || fs::remove_dir_all(dir).is_ok() {
+            return;
+        }
+    }
 }
 
-fn build_release_in_dir(dir: &Path) -> Result<()> {
-    let output = Command::new("cargo")
-        .args(["build", "--release"])
-        .current_dir(dir)
-        .output()
-        .context("Failed to execute cargo build")?;
+/// Manifest written by `plugin new --offline`; the package name is filled in afterwards
+const EMBEDDED_CARGO_TOML: &str = r#"[package]
+name = "plugin_template"
+version = "0.1.0"
+edition = "2021"
 
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("Cargo build failed:\n{}", error));
-    }
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+horizon_event_system = "0.1"
+serde = { version = "1.0", features = ["derive"] }
+serde_json = "1.0"
+tokio = { version = "1.0", features = ["full"] }
+async-trait = "0.1"
+tracing = { version = "0.1", features = ["log"] }
+"#;
 
+const EMBEDDED_GITIGNORE: &str = "/target\nCargo.lock\n";
+
+/// Lay out a new plugin from the built-in template. `src/lib.rs` is
+/// generated later, the same way as for a cloned template.
+fn write_embedded_template(plugin_dir: &Path) -> Result<()> {
+    if plugin_dir.exists() {
+        return Err(anyhow!("Directory '{}' already exists", plugin_dir.display()));
+    }
+    fs::create_dir_all(plugin_dir.join("src"))
+        .with_context(|| format!("Failed to create {}", plugin_dir.display()))?;
+    fs::write(plugin_dir.join("Cargo.toml"), EMBEDDED_CARGO_TOML)?;
+    fs::write(plugin_dir.join(".gitignore"), EMBEDDED_GITIGNORE)?;
     Ok(())
 }
 
-fn find_built_library_in_dir(plugin_dir: &Path, plugin_name: &str) -> Result<PathBuf> {
-    let target_dir = plugin_dir.join("target/release");
-    find_built_library_in_workspace(&target_dir, plugin_name)
+/// How long a template mirror is used as-is before `plugin new` fetches again
+const TEMPLATE_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Accept HTTPS, SSH (URL or scp-style), git:// and file:// remotes, or a local repository path
+fn looks_like_git_remote(url: &str) -> bool {
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return false;
+    }
+    if Path::new(url).is_dir() {
+        return true;
+    }
+    if let Some((scheme, rest)) = url.split_once("://") {
+        return matches!(scheme, "https" | "http" | "ssh" | "git" | "file") && rest.len() > 1;
+    }
+    // scp-style: [user@]host:path
+    url.split_once(':')
+        .is_some_and(|(host, path)| !host.is_empty() && !host.contains('/') && !path.is_empty())
+}
+
+/// Last path component of a repository URL, without `.git`
+fn template_repo_name(url: &str) -> &str {
+    let name = url.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(url);
+    name.strip_suffix(".git").unwrap_or(name)
 }
 
-fn find_built_library_in_workspace(target_dir: &Path, plugin_name: &str) -> Result<PathBuf> {
-    if !target_dir.exists() {
-        return Err(anyhow!("Release target directory not found for plugin {} ({}).", plugin_name, target_dir.display()));
+/// Directory name of the cached mirror for a template URL. Custom templates
+/// get a hash suffix so two repositories with the same name don't share one.
+fn template_mirror_name(url: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    if url == SAMPLE_REPO_URL {
+        return "Horizon-Plugin-Sample.git".to_string();
+    }
+    let hash = Sha256::digest(url.as_bytes());
+    let suffix: String = hash.iter().take(6).map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}.git", template_repo_name(url), suffix)
+}
+
+/// The sample repository and the local bare mirror `plugin new` copies it from
+#[derive(Clone)]
+struct TemplateSource {
+    url: String,
+    mirror: PathBuf,
+    /// Never contact the remote (`--cached`)
+    offline: bool,
+    /// Fetch even if the mirror is fresh, and fail if that's impossible (`--refresh`)
+    refresh: bool,
+}
+
+/// Clone the template into `target_dir`. git2 blocks, so the work runs on a
+/// blocking thread and stops early once `cancel` is raised.
+async fn clone_sample_repo(
+    target_dir: &Path,
+    requested_branch: Option<&str>,
+    source: &TemplateSource,
+    cancel: Arc<AtomicBool>,
+) -> Result<()> {
+    let target_dir = target_dir.to_path_buf();
+    let requested_branch = requested_branch.map(str::to_string);
+    let source = source.clone();
+    let result = {
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || clone_template(&target_dir, requested_branch.as_deref(), &source, cancel)).await?
+    };
+    if cancel.load(Ordering::SeqCst) {
+        return Err(anyhow!("Interrupted"));
+    }
+    result
+}
+
+fn clone_template(target_dir: &Path, requested_branch: Option<&str>, source: &TemplateSource, cancel: Arc<AtomicBool>) -> Result<()> {
+    use git2::build::{CheckoutBuilder, RepoBuilder};
+    use git2::{CheckoutNotificationType, FetchOptions, RemoteCallbacks};
+
+    let mirror = open_template_mirror(source, &cancel)?;
+
+    // Read branches from the mirror rather than trusting a hardcoded name
+    let default_branch = mirror.find_reference("HEAD").ok()
+        .and_then(|head| head.symbolic_target().map(|t| t.trim_start_matches("refs/heads/").to_string()));
+    let branches: Vec<String> = mirror.branches(Some(git2::BranchType::Local))?
+        .filter_map(|branch| branch.ok())
+        .filter_map(|(branch, _)| branch.name().ok().flatten().map(str::to_string))
+        .collect();
+
+    let branch = resolve_template_branch(&branches, default_branch.as_deref(), requested_branch)?;
+
+    // Clone the repository
+    let mirror_path = source.mirror.to_str()
+        .ok_or_else(|| anyhow!("Template cache path is not valid UTF-8: {}", source.mirror.display()))?;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.transfer_progress(|_| !cancel.load(Ordering::SeqCst));
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    let mut checkout = CheckoutBuilder::new();
+    checkout.notify_on(CheckoutNotificationType::UPDATED);
+    checkout.notify(|_, _, _, _, _| !cancel.load(Ordering::SeqCst));
+    let mut builder = RepoBuilder::new();
+    builder.fetch_options(fetch_options);
+    builder.with_checkout(checkout);
+    if let Some(branch) = &branch {
+        builder.branch(branch);
+    }
+    builder.clone(mirror_path, target_dir)
+        .with_context(|| format!("Failed to clone sample repository to {}", target_dir.display()))?;
+    
+    Ok(())
+}
+
+/// Open the template mirror, creating or updating it from the remote as needed
+fn open_template_mirror(source: &TemplateSource, cancel: &Arc<AtomicBool>) -> Result<git2::Repository> {
+    use git2::Repository;
+
+    let exists = source.mirror.join("HEAD").exists();
+    if source.offline {
+        if !exists {
+            return Err(anyhow!(
+                "No cached template at {}; run plugin new once without --cached while online",
+                source.mirror.display()
+            ));
+        }
+        return Ok(Repository::open_bare(&source.mirror)?);
+    }
+
+    if exists && !source.refresh && template_mirror_is_fresh(&source.mirror) {
+        return Ok(Repository::open_bare(&source.mirror)?);
     }
-    // Look for library files with common extensions
-    let extensions = if cfg!(target_os = "windows") {
-        vec!["dll"]
-    } else if cfg!(target_os = "macos") {
-        vec!["dylib"]
+
+    let repo = if exists {
+        Repository::open_bare(&source.mirror)?
     } else {
-        vec!["so"]
+        fs::create_dir_all(&source.mirror)?;
+        let repo = Repository::init_bare(&source.mirror)?;
+        repo.remote("origin", &source.url)?;
+        repo
     };
-    for entry in WalkDir::new(&target_dir).max_depth(1) {
-        let entry = entry?;
-        let path = entry.path();
-        if let Some(extension) = path.extension() {
-            if extensions.contains(&extension.to_string_lossy().as_ref()) {
-                if let Some(file_name) = path.file_name() {
-                    let name = file_name.to_string_lossy();
-                    // Match plugin library name
-                    if name.starts_with(plugin_name) || (name.starts_with("plugin_") && name.contains(&plugin_name)) {
-                        return Ok(path.to_path_buf());
-                    }
-                }
+
+    match fetch_template_mirror(&repo, cancel) {
+        Ok(()) => Ok(repo),
+        Err(e) if exists && !source.refresh => {
+            outln!("{} Could not update the cached template ({}); using the cached copy", "⚠️".yellow(), e);
+            Ok(repo)
+        }
+        Err(e) => {
+            if !exists {
+                let _ = fs::remove_dir_all(&source.mirror);
             }
+            Err(e.context(format!(
+                "Failed to reach template repository {}; check the URL, your network connection and git credentials",
+                source.url
+            )))
         }
     }
-    Err(anyhow!("Could not find built plugin library in {} for plugin {}", target_dir.display(), plugin_name))
 }
 
-fn build_release() -> Result<()> {
-    let output = Command::new("cargo")
-        .args(["build", "--release"])
-        .output()
-        .context("Failed to execute cargo build")?;
+/// Whether the mirror was fetched within `TEMPLATE_CACHE_MAX_AGE`
+fn template_mirror_is_fresh(mirror: &Path) -> bool {
+    fs::metadata(mirror.join("FETCH_HEAD"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < TEMPLATE_CACHE_MAX_AGE)
+}
 
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("Cargo build failed:\n{}", error));
-    }
+/// Mirror every branch of origin and point HEAD at its default branch,
+/// authenticating like the repo commands so SSH and private HTTPS templates work.
+/// The transfer stops once `cancel` is raised.
+fn fetch_template_mirror(repo: &git2::Repository, cancel: &Arc<AtomicBool>) -> Result<()> {
+    use git2::{Direction, FetchOptions};
+
+    let mut remote = repo.find_remote("origin")?;
+    let url = remote.url().unwrap_or("origin").to_string();
+    let config = repo.config()?;
+    let credentials = CredentialCache::default();
+    let auth_error = |e: anyhow::Error| match e.downcast_ref::<git2::Error>() {
+        Some(error) if error.code() == git2::ErrorCode::Auth => {
+            anyhow!("Authentication failed for template {}: {}", url, error.message())
+        }
+        _ => e,
+    };
+
+    let default_branch = credentials
+        .with_credentials(&config, |callbacks| {
+            let connection = remote.connect_auth(Direction::Fetch, Some(callbacks), None)?;
+            Ok(connection.default_branch().ok().and_then(|name| name.as_str().map(str::to_string)))
+        })
+        .map_err(auth_error)?;
 
+    credentials
+        .with_credentials(&config, |mut callbacks| {
+            let cancel = cancel.clone();
+            callbacks.transfer_progress(move |_| !cancel.load(Ordering::SeqCst));
+            let mut options = FetchOptions::new();
+            options.prune(git2::FetchPrune::On);
+            options.remote_callbacks(callbacks);
+            remote.fetch(&["+refs/heads/*:refs/heads/*"], Some(&mut options), None)
+        })
+        .map_err(auth_error)?;
+
+    if let Some(default_branch) = default_branch {
+        repo.set_head(&default_branch)?;
+    }
     Ok(())
 }
 
-fn find_built_library() -> Result<PathBuf> {
-    let target_dir = PathBuf::from("target/release");
+/// Pick the sample branch to check out: the requested one if it exists,
+/// otherwise the remote's default. `None` leaves the choice to the clone.
+fn resolve_template_branch(
+    branches: &[String],
+    default_branch: Option<&str>,
+    requested: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(requested) = requested else {
+        return Ok(default_branch.map(str::to_string));
+    };
+    if branches.iter().any(|b| b == requested) {
+        return Ok(Some(requested.to_string()));
+    }
+
+    let default_note = default_branch
+        .map(|b| format!(" (default: {})", b))
+        .unwrap_or_default();
+    Err(anyhow!(
+        "Template branch '{}' does not exist in the sample repository. Available branches: {}{}",
+        requested, branches.join(", "), default_note
+    ))
+}
+
+fn update_cargo_toml(
+    plugin_dir: &Path,
+    plugin_name: &str,
+    dependencies: &[(String, String)],
+    overrides: &[(String, String)],
+) -> Result<()> {
+    let cargo_path = plugin_dir.join("Cargo.toml");
+    let content = fs::read_to_string(&cargo_path)?;
     
-    if !target_dir.exists() {
-        return Err(anyhow!("Release target directory not found"));
+    let mut doc = content.parse::<Document>()?;
+    
+    // Update package name
+    doc["package"]["name"] = value(format!("plugin_{}", plugin_name));
+
+    if !dependencies.is_empty() {
+        let deps = doc.entry("dependencies")
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("[dependencies] in {} is not a table", cargo_path.display()))?;
+        for (name, version) in dependencies {
+            deps.insert(name, value(version.as_str()));
+        }
     }
 
-    // Look for library files with common extensions
-    let extensions = if cfg!(target_os = "windows") {
-        vec!["dll"]
-    } else if cfg!(target_os = "macos") {
-        vec!["dylib"]
-    } else {
-        vec!["so"]
+    for (key, raw) in overrides {
+        set_dotted_key(&mut doc, key, parse_toml_value(raw))
+            .with_context(|| format!("Failed to apply --set {}={}", key, raw))?;
+    }
+    
+    fs::write(cargo_path, doc.to_string())?;
+    Ok(())
+}
+
+/// Split a `--set` argument into its dotted key and raw value
+fn parse_cargo_override(arg: &str) -> std::result::Result<(String, String), String> {
+    let (key, raw) = arg.split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", arg))?;
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(format!("invalid key '{}'", key));
+    }
+    Ok((key.to_string(), raw.trim().to_string()))
+}
+
+/// Normalize a `--signal` argument to what `kill -<signal>` accepts, e.g. `SIGUSR1` -> `USR1`
+fn parse_signal(arg: &str) -> std::result::Result<String, String> {
+    let signal = arg.trim().to_ascii_uppercase();
+    let signal = signal.strip_prefix("SIG").unwrap_or(&signal);
+    if signal.is_empty() || !signal.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("invalid signal '{}': use a name like HUP or USR1, or a number", arg));
+    }
+    Ok(signal.to_string())
+}
+
+/// Split a `--dependency` argument into a crate name and, if given, a version requirement
+fn parse_dependency_arg(arg: &str) -> std::result::Result<(String, Option<String>), String> {
+    let (name, version) = match arg.split_once('=') {
+        Some((name, version)) => (name.trim(), Some(version.trim())),
+        None => (arg.trim(), None),
     };
+    if version == Some("") {
+        return Err(format!("missing version after '=' in '{}'", arg));
+    }
+    // Same rules crates.io applies to crate names
+    let valid_name = name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(format!(
+            "invalid crate name '{}': use ASCII letters, digits, '-' and '_', starting with a letter",
+            name
+        ));
+    }
+    Ok((name.to_string(), version.map(str::to_string)))
+}
 
-    for entry in WalkDir::new(&target_dir).max_depth(1) {
-        let entry = entry?;
-        let path = entry.path();
-        
-        if let Some(extension) = path.extension() {
-            if extensions.contains(&extension.to_string_lossy().as_ref()) {
-                if let Some(file_name) = path.file_name() {
-                    let name = file_name.to_string_lossy();
-                    if name.starts_with("plugin_") || name.contains("plugin") {
-                        return Ok(path.to_path_buf());
-                    }
+/// Fill in the latest crates.io version for `--dependency` arguments given
+/// without one, like `cargo add` does. A wildcard requirement would be simpler
+/// but crates.io rejects it when the plugin is published.
+async fn resolve_dependency_versions(
+    dependencies: &[(String, Option<String>)],
+    offline: bool,
+) -> Result<Vec<(String, String)>> {
+    let client = reqwest::Client::new();
+    let mut resolved = Vec::with_capacity(dependencies.len());
+    for (name, version) in dependencies {
+        let version = match version {
+            Some(version) => version.clone(),
+            None if offline => {
+                return Err(anyhow!(
+                    "--dependency {0} needs a version with --offline, e.g. --dependency {0}=1",
+                    name
+                ));
+            }
+            None => {
+                let version = latest_crate_version(&client, name).await.with_context(|| format!(
+                    "Could not look up the latest version of '{0}'; pass one explicitly, e.g. --dependency {0}=1",
+                    name
+                ))?;
+                statusln!("📦 Using {} {} (latest on crates.io)", name, version);
+                version
+            }
+        };
+        resolved.push((name.clone(), version));
+    }
+    Ok(resolved)
+}
+
+/// Newest stable (else newest) version of a crate on crates.io
+async fn latest_crate_version(client: &reqwest::Client, name: &str) -> Result<String> {
+    let response = client
+        .get(format!("https://crates.io/api/v1/crates/{}", name))
+        .header("User-Agent", concat!("fbcli/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!("crate '{}' does not exist on crates.io", name));
+    }
+    let body: serde_json::Value = response.error_for_status()?.json().await?;
+    crate_version_from_response(&body).ok_or_else(|| anyhow!("crates.io returned no version for '{}'", name))
+}
+
+fn crate_version_from_response(body: &serde_json::Value) -> Option<String> {
+    let krate = body.get("crate")?;
+    ["max_stable_version", "max_version"]
+        .iter()
+        .filter_map(|key| krate.get(*key).and_then(|v| v.as_str()))
+        .find(|version| !version.is_empty())
+        .map(str::to_string)
+}
+
+/// Parse a value as TOML (`["a", "b"]`, `true`, `"text"`), falling back to a bare string
+fn parse_toml_value(raw: &str) -> toml_edit::Value {
+    raw.parse::<toml_edit::Value>().unwrap_or_else(|_| raw.into())
+}
+
+/// Set `a.b.c` in the document, creating intermediate tables as needed
+fn set_dotted_key(doc: &mut Document, key: &str, new_value: toml_edit::Value) -> Result<()> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().ok_or_else(|| anyhow!("empty key"))?;
+
+    let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for part in parts {
+        table = table.entry(part)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("'{}' is not a table", part))?;
+    }
+    table.insert(last, value(new_value));
+    Ok(())
+}
+
+/// Add or remove the tokio test dependency to match the requested runtime setup.
+/// tokio is only for test harnesses, so it never goes into the shipped library's
+/// `[dependencies]`, and a template's own tokio dependency is left alone.
+fn apply_async_runtime(plugin_dir: &Path, runtime: AsyncRuntime) -> Result<()> {
+    let cargo_path = plugin_dir.join("Cargo.toml");
+    let content = fs::read_to_string(&cargo_path)?;
+    let mut doc = content.parse::<Document>()?;
+
+    match runtime {
+        AsyncRuntime::Tokio => {
+            let in_dependencies = doc.get("dependencies")
+                .and_then(|deps| deps.as_table_like())
+                .is_some_and(|deps| deps.contains_key("tokio"));
+            // Otherwise the tests can already use the template's tokio
+            if !in_dependencies {
+                let dev_deps = doc.entry("dev-dependencies")
+                    .or_insert(toml_edit::table())
+                    .as_table_like_mut()
+                    .ok_or_else(|| anyhow!("[dev-dependencies] in {} is not a table", cargo_path.display()))?;
+                if !dev_deps.contains_key("tokio") {
+                    let mut tokio = toml_edit::InlineTable::new();
+                    tokio.insert("version", "1.0".into());
+                    let mut features = toml_edit::Array::new();
+                    features.push("full");
+                    tokio.insert("features", features.into());
+                    dev_deps.insert("tokio", value(tokio));
                 }
             }
         }
+        AsyncRuntime::None => {
+            if let Some(dev_deps) = doc.get_mut("dev-dependencies").and_then(|t| t.as_table_like_mut()) {
+                dev_deps.remove("tokio");
+            }
+        }
     }
 
-    Err(anyhow!("Could not find built plugin library in target/release"))
+    fs::write(cargo_path, doc.to_string())?;
+    Ok(())
 }
 
-fn copy_to_horizon_plugins(lib_path: &Path, horizon_path: &Path) -> Result<()> {
-    let plugins_dir = horizon_path.join("plugins");
+fn update_plugin_code(plugin_dir: &Path, plugin_name: &str, struct_name: &str, tokio_tests: bool) -> Result<()> {
+    let lib_path = plugin_dir.join("src/lib.rs");
     
-    // Create plugins directory if it doesn't exist
-    if !plugins_dir.exists() {
-        fs::create_dir_all(&plugins_dir)
-            .with_context(|| format!("Failed to create plugins directory: {}", plugins_dir.display()))?;
+    // Create a basic version of the greeter plugin with the new name
+    let mut new_content = create_basic_plugin_template(plugin_name, struct_name);
+    if tokio_tests {
+        new_content.push_str(&create_tokio_test_scaffold(plugin_name, struct_name));
     }
-
-    let file_name = lib_path.file_name()
-        .ok_or_else(|| anyhow!("Invalid library file path"))?;
     
-    let target_path = plugins_dir.join(file_name);
-    
-    fs::copy(lib_path, &target_path)
-        .with_context(|| format!("Failed to copy plugin to {}", target_path.display()))?;
+    fs::write(lib_path, new_content)?;
+    Ok(())
+}
+
+fn create_tokio_test_scaffold(plugin_name: &str, struct_name: &str) -> String {
+    format!(r#"
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    #[tokio::test]
+    async fn plugin_reports_name_and_version() {{
+        let plugin = {0}::new();
+        assert_eq!(plugin.name(), "{1}");
+        assert_eq!(plugin.version(), "1.0.0");
+    }}
+}}
+"#, struct_name, plugin_name)
+}
+
+/// Struct name `plugin new` generates unless `--name` is given, e.g. `ChatLogPlugin`
+fn default_struct_name(plugin_name: &str) -> String {
+    format!("{}Plugin", to_pascal_case(plugin_name))
+}
+
+fn create_basic_plugin_template(plugin_name: &str, struct_name: &str) -> String {
+    format!(r#"use async_trait::async_trait;
+use horizon_event_system::{{
+    create_simple_plugin, current_timestamp, EventSystem, LogLevel,
+    PluginError, ServerContext, SimplePlugin,
+}};
+use serde::{{Deserialize, Serialize}};
+use std::sync::Arc;
+use tracing::{{error, info, warn}};
+
+/// {}
+pub struct {} {{
+    name: String,
+}}
+
+impl {} {{
+    pub fn new() -> Self {{
+        info!("🔧 {}: Creating new instance");
+        Self {{
+            name: "{}".to_string(),
+        }}
+    }}
+}}
+
+#[async_trait]
+impl SimplePlugin for {} {{
+    fn name(&self) -> &str {{
+        &self.name
+    }}
+
+    fn version(&self) -> &str {{
+        "1.0.0"
+    }}
+
+    async fn register_handlers(&mut self, _events: Arc<EventSystem>) -> Result<(), PluginError> {{
+        info!("🔧 {}: Registering event handlers...");
+        
+        // TODO: Register your event handlers here
+        // Example:
+        // register_handlers!(events; core {{
+        //     "your_event" => |event: serde_json::Value| {{
+        //         info!("Received event: {{:?}}", event);
+        //         Ok(())
+        //     }}
+        // }})?;
+        
+        info!("🔧 {}: ✅ All handlers registered successfully!");
+        Ok(())
+    }}
+
+    async fn on_init(&mut self, context: Arc<dyn ServerContext>) -> Result<(), PluginError> {{
+        context.log(
+            LogLevel::Info,
+            "🔧 {}: Starting up!",
+        );
+
+        // TODO: Add your initialization logic here
+        
+        info!("🔧 {}: ✅ Initialization complete!");
+        Ok(())
+    }}
+
+    async fn on_shutdown(&mut self, context: Arc<dyn ServerContext>) -> Result<(), PluginError> {{
+        context.log(
+            LogLevel::Info,
+            "🔧 {}: Shutting down!",
+        );
+
+        // TODO: Add your cleanup logic here
+
+        info!("🔧 {}: ✅ Shutdown complete!");
+        Ok(())
+    }}
+}}
+
+// Create the plugin using the macro
+create_simple_plugin!({});
+"#, 
+        struct_name,       // Comment
+        struct_name,       // struct name
+        struct_name,       // impl block
+        struct_name,       // log message
+        plugin_name,       // name field
+        struct_name,       // trait impl
+        struct_name,       // register_handlers log
+        struct_name,       // register_handlers success log
+        struct_name,       // on_init log
+        struct_name,       // on_init success log
+        struct_name,       // on_shutdown log
+        struct_name,       // on_shutdown success log
+        struct_name        // macro call
+    )
+}
+
+const LIFECYCLE_TEST_PATH: &str = "tests/plugin_lifecycle.rs";
+
+fn test_plugin(args: TestArgs) -> Result<()> {
+    check_cargo_available()?;
+    let target = detect_plugin_crate(current_dir()?, args.plugin)?;
+
+    outln!("🔍 Testing plugin {} in {}", style(&target.package_name).cyan().bold(), style(target.plugin_dir.display()).yellow());
+    outln!();
+
+    // Inherit stdio so test output streams as it runs
+    let status = Command::new("cargo")
+        .arg("test")
+        .args(&args.cargo_args)
+        .current_dir(&target.plugin_dir)
+        .status()
+        .context("Failed to execute cargo test")?;
+    if !status.success() {
+        return Err(anyhow!("Tests failed for {}", target.package_name));
+    }
+
+    outln!();
+    outln!("{}", "✅ All tests passed!".green().bold());
+    Ok(())
+}
+
+fn scaffold_plugin_test(args: ScaffoldTestArgs) -> Result<()> {
+    let plugin_dir = args.path.unwrap_or_else(|| PathBuf::from("."));
+    let manifest_path = plugin_dir.join("Cargo.toml");
+    let test_path = plugin_dir.join(LIFECYCLE_TEST_PATH);
+
+    if test_path.exists() && !args.force {
+        return Err(anyhow!("{} already exists; pass --force to overwrite it", test_path.display()));
+    }
 
+    let content = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let mut doc = content.parse::<Document>()?;
+    let package_name = doc.get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| anyhow!("Cargo.toml missing [package] name field ({}).", manifest_path.display()))?
+        .to_string();
+
+    add_test_harness_dependencies(&mut doc)?;
+    fs::write(&manifest_path, doc.to_string())?;
+
+    // Plugins created with `plugin new --name` don't follow the default struct name
+    let crate_ident = package_name.replace('-', "_");
+    let struct_name = exported_struct_name(&plugin_dir)
+        .unwrap_or_else(|| default_struct_name(crate_ident.strip_prefix("plugin_").unwrap_or(&crate_ident)));
+
+    fs::create_dir_all(plugin_dir.join("tests"))?;
+    fs::write(&test_path, create_lifecycle_test_template(&package_name, &struct_name))?;
+
+    outln!("{}", "✅ Test harness created!".green().bold());
+    outln!("📄 Test: {}", style(test_path.display()).yellow());
+    outln!("📝 Updated {} (rlib crate-type, dev-dependencies)", manifest_path.display());
+    outln!();
+    outln!("{}", "Next steps:".bold());
+    outln!("  cargo test");
+    Ok(())
+}
+
+/// Integration tests can only link an rlib, and the harness needs an async test runtime
+fn add_test_harness_dependencies(doc: &mut Document) -> Result<()> {
+    let crate_types = doc.entry("lib")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow!("[lib] is not a table"))?
+        .entry("crate-type")
+        .or_insert(value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("[lib] crate-type is not an array"))?;
+    if crate_types.is_empty() {
+        crate_types.push("cdylib");
+    }
+    if !crate_types.iter().any(|t| t.as_str() == Some("rlib")) {
+        crate_types.push("rlib");
+    }
+
+    let has_dependency = |doc: &Document, name: &str| {
+        doc.get("dependencies")
+            .and_then(|deps| deps.as_table_like())
+            .is_some_and(|deps| deps.contains_key(name))
+    };
+    let need_async_trait = !has_dependency(doc, "async-trait");
+
+    let dev_deps = doc.entry("dev-dependencies")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow!("[dev-dependencies] is not a table"))?;
+    if !dev_deps.contains_key("tokio") {
+        let mut tokio = toml_edit::InlineTable::new();
+        tokio.insert("version", "1.0".into());
+        let mut features = toml_edit::Array::new();
+        features.push("macros");
+        features.push("rt-multi-thread");
+        tokio.insert("features", features.into());
+        dev_deps.insert("tokio", value(tokio));
+    }
+    if need_async_trait && !dev_deps.contains_key("async-trait") {
+        dev_deps.insert("async-trait", value("0.1"));
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The struct `src/lib.rs` passes to `create_simple_plugin!`, if it can be found
+fn exported_struct_name(plugin_dir: &Path) -> Option<String> {
+    let lib = fs::read_to_string(plugin_dir.join("src/lib.rs")).ok()?;
+    let (_, rest) = lib.split_once("create_simple_plugin!(")?;
+    let (name, _) = rest.split_once(')')?;
+    parse_struct_name(name.trim()).ok()
+}
+
+fn create_lifecycle_test_template(package_name: &str, struct_name: &str) -> String {
+    let crate_ident = package_name.replace('-', "_");
+
+    format!(r#"//! Lifecycle tests that run the plugin without a Horizon server.
+//!
+//! `MockContext` implements `ServerContext` by recording log lines. If your
+//! horizon_event_system version adds methods to the trait, extend it here.
+
+use async_trait::async_trait;
+use horizon_event_system::{{
+    EventSystem, LogLevel, PlayerId, RegionId, ServerContext, ServerError, SimplePlugin,
+}};
+use std::sync::{{Arc, Mutex}};
+use {0}::{1};
+
+#[derive(Debug)]
+struct MockContext {{
+    events: Arc<EventSystem>,
+    logs: Mutex<Vec<String>>,
+}}
+
+impl MockContext {{
+    fn new(events: Arc<EventSystem>) -> Self {{
+        Self {{ events, logs: Mutex::new(Vec::new()) }}
+    }}
+
+    fn logs(&self) -> Vec<String> {{
+        self.logs.lock().unwrap().clone()
+    }}
+}}
+
+#[async_trait]
+impl ServerContext for MockContext {{
+    fn events(&self) -> Arc<EventSystem> {{
+        self.events.clone()
+    }}
+
+    fn region_id(&self) -> RegionId {{
+        RegionId::default()
+    }}
+
+    fn log(&self, _level: LogLevel, message: &str) {{
+        self.logs.lock().unwrap().push(message.to_string());
+    }}
+
+    async fn send_to_player(&self, _player_id: PlayerId, _data: &[u8]) -> Result<(), ServerError> {{
+        Ok(())
+    }}
+
+    async fn broadcast(&self, _data: &[u8]) -> Result<(), ServerError> {{
+        Ok(())
+    }}
+}}
+
+#[tokio::test]
+async fn plugin_runs_full_lifecycle() {{
+    let events = Arc::new(EventSystem::new());
+    let context = Arc::new(MockContext::new(events.clone()));
+    let mut plugin = {1}::new();
+
+    assert!(!plugin.name().is_empty());
+    assert!(!plugin.version().is_empty());
+
+    plugin.register_handlers(events).await.expect("register_handlers failed");
+    plugin.on_init(context.clone()).await.expect("on_init failed");
+    plugin.on_shutdown(context.clone()).await.expect("on_shutdown failed");
+
+    let logs = context.logs();
+    assert!(logs.iter().any(|line| line.contains("Starting up")), "on_init did not log: {{:?}}", logs);
+    assert!(logs.iter().any(|line| line.contains("Shutting down")), "on_shutdown did not log: {{:?}}", logs);
+}}
+"#, crate_ident, struct_name)
+}
+
+/// Capitalize each word of a plugin name. Words that are already mixed-case
+/// (`XMLParser`, `myPlugin`) keep their internal capitals; the rest are
+/// lowercased after the first letter, so `HTTP` and `http` both become `Http`.
+fn to_pascal_case(s: &str) -> String {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|word| {
+            let mixed_case = word.chars().any(char::is_uppercase) && word.chars().any(char::is_lowercase);
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) if mixed_case => first.to_uppercase().collect::<String>() + chars.as_str(),
+                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+            }
+        })
+        .collect()
+}
+
+fn cleanup_plugin_directory(plugin_dir: &Path, keep_readme: bool) -> Result<()> {
+    // Remove .git directory
+    let git_dir = plugin_dir.join(".git");
+    if git_dir.exists() {
+        fs::remove_dir_all(git_dir)?;
+    }
+    
+    // Remove README if it exists (they can create their own)
+    let readme_path = plugin_dir.join("README.md");
+    if readme_path.exists() && !keep_readme {
+        fs::remove_file(readme_path)?;
+    }
+    
+    Ok(())
+}
+
+/// Substitute the plugin's names into a template README's placeholders
+fn fill_readme_placeholders(plugin_dir: &Path, plugin_name: &str, struct_name: &str) -> Result<()> {
+    let readme_path = plugin_dir.join("README.md");
+    if !readme_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&readme_path)?;
+    let filled = content
+        .replace("{{plugin_name}}", plugin_name)
+        .replace("{{crate_name}}", &format!("plugin_{}", plugin_name))
+        .replace("{{struct_name}}", struct_name);
+    if filled != content {
+        fs::write(&readme_path, filled)?;
+    }
+    Ok(())
+}
+
+fn init_plugin_repository(plugin_dir: &Path) -> Result<()> {
+    use git2::{IndexAddOption, Repository, Signature};
+
+    let repo = Repository::init(plugin_dir)
+        .with_context(|| format!("Failed to initialize git repository in {}", plugin_dir.display()))?;
+
+    let mut index = repo.index()?;
+    index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    // Fall back to a generic identity if the user has no git config
+    let signature = repo.signature()
+        .or_else(|_| Signature::now("fbcli", "fbcli@localhost"))?;
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial commit from fbcli", &tree, &[])?;
+
+    Ok(())
+}
+
+/// The plugin crate a build was asked for, resolved from the working directory
+struct BuildTarget {
+    current_dir: PathBuf,
+    plugin_dir: PathBuf,
+    package_name: String,
+    in_horizon_root: bool,
+    /// Given with `--manifest-path`: cargo is pointed at it instead of run in `plugin_dir`
+    manifest_path: Option<PathBuf>,
+}
+
+impl BuildTarget {
+    /// Arguments that make cargo act on this crate: `--manifest-path <path> -p <package>`
+    /// for `--manifest-path`, nothing when cargo runs in the plugin's directory
+    fn cargo_args(&self) -> Vec<std::ffi::OsString> {
+        match &self.manifest_path {
+            Some(manifest) => vec![
+                "--manifest-path".into(),
+                manifest.into(),
+                "-p".into(),
+                self.package_name.clone().into(),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// The crate's Cargo.toml
+    fn manifest(&self) -> PathBuf {
+        self.manifest_path.clone().unwrap_or_else(|| self.plugin_dir.join("Cargo.toml"))
+    }
+}
+
+fn build_plugin(args: &BuildArgs) -> Result<()> {
+    statusln!("🔨 Building Horizon plugin...");
+    let target = resolve_build_target(args)?;
+    let result = build_and_deploy(args, &target);
+    if args.notify {
+        notify_build_finished(&target.package_name, &result);
+    }
+    result
+}
+
+/// Desktop notification for `--notify`. Failing to show one (no notification
+/// service, unsupported desktop) never fails the build.
+fn notify_build_finished(package_name: &str, result: &Result<()>) {
+    let (summary, body) = match result {
+        Ok(()) => (format!("✅ {} built", package_name), "The plugin built successfully".to_string()),
+        Err(e) => (format!("❌ {} failed to build", package_name), format!("{:#}", e).lines().next().unwrap_or_default().to_string()),
+    };
+    let shown = notify_rust::Notification::new()
+        .appname("fbcli")
+        .summary(&summary)
+        .body(&body)
+        .show();
+    if let Err(e) = shown {
+        debugln!("Could not show a desktop notification: {}", e);
+    }
+}
+
+fn resolve_build_target(args: &BuildArgs) -> Result<BuildTarget> {
+    if !args.no_copy && args.horizon_path.is_none() && args.no_default_horizon_path {
+        return Err(anyhow!(
+            "No Horizon path given and the ../Horizon fallback is disabled; pass --horizon-path, set FBCLI_HORIZON_PATH or horizon_path in the config file, or use --no-copy"
+        ));
+    }
+    let current_dir = current_dir()?;
+    if let Some(manifest_path) = &args.manifest_path {
+        let manifest_path = fs::canonicalize(manifest_path)
+            .with_context(|| format!("Manifest not found: {}", manifest_path.display()))?;
+        let package_name = read_package_name(&manifest_path)?;
+        let plugin_dir = manifest_path.parent()
+            .ok_or_else(|| anyhow!("Invalid manifest path: {}", manifest_path.display()))?
+            .to_path_buf();
+        return Ok(BuildTarget {
+            current_dir,
+            plugin_dir,
+            package_name,
+            in_horizon_root: false,
+            manifest_path: Some(manifest_path),
+        });
+    }
+
+    // Prefer positional plugin argument, fallback to --plugin
+    let plugin = args.plugin.clone().or_else(|| args.plugin_flag.clone());
+    detect_plugin_crate(current_dir, plugin)
+}
+
+/// Find the plugin crate from the working directory: the crate itself, or
+/// `plugin` under `crates/` when run from the Horizon repo root
+fn detect_plugin_crate(current_dir: PathBuf, plugin: Option<String>) -> Result<BuildTarget> {
+    // Determine if we're in Horizon repo root or plugin crate dir
+    let cargo_toml = current_dir.join("Cargo.toml");
+    let crates_dir = current_dir.join("crates");
+    let in_plugin_dir = cargo_toml.exists();
+    let in_horizon_root = crates_dir.exists();
+
+    let (plugin_dir, package_name) = {
+        // Use directory name for plugin detection, but use package name for DLL search
+        let dir_name = current_dir.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if dir_name == "plugin_system" {
+            return Err(anyhow!("plugin_system is not a buildable plugin crate"));
+        }
+        if in_plugin_dir && dir_name.starts_with("plugin_") {
+            debugln!("Detected plugin crate by directory name: {}", dir_name);
+            let pkg_name = read_package_name(&current_dir.join("Cargo.toml"))?;
+            if let Some(requested) = plugin.as_deref() {
+                check_plugin_argument_matches(requested, &pkg_name)?;
+            }
+            (current_dir.clone(), pkg_name)
+        } else if in_plugin_dir && read_horizon_metadata(&cargo_toml)?.is_some() {
+            debugln!("Detected plugin crate by [package.metadata.horizon]: {}", dir_name);
+            let pkg_name = read_package_name(&current_dir.join("Cargo.toml"))?;
+            if let Some(requested) = plugin.as_deref() {
+                check_plugin_argument_matches(requested, &pkg_name)?;
+            }
+            (current_dir.clone(), pkg_name)
+        } else if in_horizon_root {
+            let plugin_arg = plugin.ok_or_else(|| anyhow!("--plugin argument required when in Horizon repo root"))?;
+            let plugin_path = find_plugin_crate(&crates_dir, &plugin_arg)?;
+            let pkg_name = read_package_name(&plugin_path.join("Cargo.toml"))?;
+            (plugin_path, pkg_name)
+        } else {
+            return Err(anyhow!("Not in a plugin crate directory or Horizon repo root"));
+        }
+    };
+
+    Ok(BuildTarget { current_dir, plugin_dir, package_name, in_horizon_root, manifest_path: None })
+}
+
+fn build_and_deploy(args: &BuildArgs, target: &BuildTarget) -> Result<()> {
+    let BuildTarget { plugin_dir, .. } = target;
+    let no_copy = args.no_copy;
+
+    // Create progress bar
+    let steps = if no_copy { 2 } else { 3 };
+    let pb = ProgressBar::new(if args.copy_only { steps - 1 } else { steps });
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+            .unwrap()
+            .progress_chars("##-"),
+    );
+    if !ui::shows_progress() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+
+    // Fail before building if the server to notify isn't running
+    if let Some(pid) = args.signal_pid {
+        check_process_running(pid)?;
+    }
+    if args.strip && args.target_triple() != HOST_TARGET {
+        return Err(anyhow!(
+            "--strip uses this machine's `strip`, which can't handle a library built for {}",
+            args.target_triple()
+        ));
+    }
+
+    let features = if args.features_from_metadata {
+        features_from_metadata(&target.manifest())?
+    } else {
+        Vec::new()
+    };
+
+    if args.clean_first {
+        pb.set_message("Cleaning previous build artifacts...");
+        // Measure the profile's artifacts before and after to report what cargo removed
+        let artifacts = cargo_target_dir(&target.manifest()).ok().map(|dir| dir.join(args.artifact_dir()));
+        let before = artifacts.as_deref().and_then(|dir| dir_size(dir).ok());
+        let scope = clean_package(target, &args.cargo_build_args())?;
+        let freed = before
+            .map(|before| {
+                // A full clean can remove the directory itself
+                let after = artifacts.as_deref().and_then(|dir| dir_size(dir).ok()).unwrap_or(0);
+                format!(" ({} freed)", format_bytes(before.saturating_sub(after)))
+            })
+            .unwrap_or_default();
+        pb.suspend(|| match scope {
+            CleanScope::Package => outln!("🔧 Cleaned previous artifacts of {}{}", target.package_name, freed),
+            CleanScope::Everything => outln!(
+                "⚠️  `cargo clean -p {}` failed; cleaned all {} artifacts instead{}",
+                target.package_name, args.profile().dir_name(), freed
+            ),
+        });
+    }
+
+    // Step 1: Build the plugin
+    if !args.copy_only {
+        let mut mode = format!("{} mode", args.profile().dir_name());
+        if let Some(triple) = &args.target_triple {
+            mode.push_str(&format!(", target {}", triple));
+        }
+        if !features.is_empty() {
+            mode.push_str(&format!(", features: {}", features.join(", ")));
+        }
+        pb.set_message(format!("Building plugin ({})...", mode));
+        build_package(target, &args.cargo_build_args(), &features, ui::is_verbose().then_some(&pb))?;
+        pb.inc(1);
+    }
+
+    // Step 2: Find the built library
+    pb.set_message("Locating built library...");
+    let lib_path = match locate_built_library(args, target) {
+        Err(e) if args.copy_only => {
+            let build_command = ["cargo", "build"].into_iter().chain(args.cargo_build_args()).collect::<Vec<_>>();
+            return Err(e.context(format!("--copy-only needs an existing build; run `{}` first", build_command.join(" "))));
+        }
+        lib_path => lib_path?,
+    };
+    pb.inc(1);
+
+    let stripped = if args.strip {
+        pb.set_message("Stripping symbols...");
+        Some(strip_library(&lib_path)?)
+    } else {
+        None
+    };
+    // What gets checked and deployed: the stripped copy, if there is one
+    let deploy_path = stripped.as_ref().map_or(&lib_path, |stripped| &stripped.path);
+
+    if args.runtime_check {
+        if args.target_triple() != HOST_TARGET {
+            return Err(anyhow!(
+                "--runtime-check can't load a library built for {} on this {} machine",
+                args.target_triple(), HOST_TARGET
+            ));
+        }
+        pb.set_message("Instantiating plugin in a separate process...");
+        runtime_check(deploy_path)?;
+    }
+
+    // Step 3: Copy to Horizon plugins directory (if not skipped)
+    let mut copied_symbols = Vec::new();
+    let mut copied_manifest = None;
+    let mut copied_to = None;
+    if !no_copy {
+        pb.set_message("Copying to Horizon plugins directory...");
+        let target_path = args.horizon_path.clone().unwrap_or_else(|| PathBuf::from("../Horizon"));
+        let deploy_name = if args.name_from_metadata {
+            Some(deploy_name_from_metadata(plugin_dir, &lib_path)?)
+        } else {
+            None
+        };
+        let deployed = copy_to_horizon_plugins(deploy_path, &target_path, deploy_name.as_deref(), args.wait_for_lock)?;
+        if args.copy_symbols {
+            copied_symbols = copy_debug_symbols(&lib_path, &target_path.join("plugins"))?;
+        }
+        if args.copy_manifest {
+            let manifest = BuildManifest::for_library(plugin_dir, &deployed, args.profile(), args.target_triple())?;
+            copied_manifest = Some(manifest.write_next_to(&deployed)?);
+        }
+        copied_to = Some(deployed);
+        pb.inc(1);
+    }
+
+    let signaled = match args.signal_pid {
+        Some(pid) => {
+            pb.set_message("Signaling the server to reload...");
+            signal_process(pid, &args.signal)?
+        }
+        None => false,
+    };
+
+    let done = if args.copy_only { "Plugin copied successfully!" } else { "Plugin built successfully!" };
+    pb.finish_with_message(ui::render(&format!("✅ {}", done)));
+
+    if args.print_artifact_path {
+        // Bypasses the quiet mode that hides everything else
+        println!("{}", fs::canonicalize(deploy_path)?.display());
+    }
+
+    outln!();
+    outln!("{}", format!("🎉 {}", done).green().bold());
+    outln!("📄 Library: {} ({} build)", style(lib_path.display()).yellow(), args.profile().dir_name());
+    if let Some(stripped) = &stripped {
+        outln!(
+            "🔧 Stripped: {} -> {} ({})",
+            format_bytes(stripped.before),
+            format_bytes(stripped.after),
+            style(stripped.path.display()).yellow()
+        );
+    }
+
+    if let Some(copied_to) = copied_to {
+        outln!("📁 Copied to: {}", style(copied_to.display()).yellow());
+        for symbols in &copied_symbols {
+            outln!("📄 Debug symbols: {}", style(symbols.display()).yellow());
+        }
+        if args.copy_symbols && copied_symbols.is_empty() {
+            outln!("⚠️  No debug symbols found next to {}", lib_path.display());
+        }
+        if let Some(manifest) = copied_manifest {
+            outln!("📋 Manifest: {}", style(manifest.display()).yellow());
+        }
+    }
+    if let (Some(pid), true) = (args.signal_pid, signaled) {
+        outln!("🔄 Sent SIG{} to process {}", args.signal, pid);
+    }
+
+    outln!();
+    Ok(())
+}
+
+/// Plugin metadata written next to a deployed library by `--copy-manifest`, so
+/// a server can discover plugins without loading them
+#[derive(Debug, Serialize)]
+struct BuildManifest {
+    name: String,
+    /// `None` when the version is inherited from the workspace
+    version: Option<String>,
+    /// File name of the library in the plugins directory
+    library: String,
+    sha256: String,
+    target: String,
+    profile: String,
+    built_at: String,
+}
+
+impl BuildManifest {
+    fn for_library(plugin_dir: &Path, library: &Path, profile: BuildProfile, target: &str) -> Result<Self> {
+        let cargo_toml = plugin_dir.join("Cargo.toml");
+        let version = read_package_version(&cargo_toml)?;
+        Ok(Self {
+            name: read_package_name(&cargo_toml)?,
+            version: (version != "workspace" && version != "-").then_some(version),
+            library: library.file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| anyhow!("Invalid library path: {}", library.display()))?
+                .to_string(),
+            sha256: sha256_file(library)?,
+            target: target.to_string(),
+            profile: profile.dir_name().to_string(),
+            built_at: Local::now().to_rfc3339(),
+        })
+    }
+
+    /// Write `<library>.manifest.json` next to the library, returning its path
+    fn write_next_to(&self, library: &Path) -> Result<PathBuf> {
+        let path = library.with_file_name(format!("{}.manifest.json", self.library));
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Find the library the build of the target produced, in the selected profile's target dir
+fn locate_built_library(args: &BuildArgs, target: &BuildTarget) -> Result<PathBuf> {
+    let BuildTarget { current_dir, plugin_dir, package_name, in_horizon_root, manifest_path } = target;
+    let profile_dir = Path::new("target").join(args.artifact_dir());
+    let triple = args.target_triple();
+    if let Some(manifest_path) = manifest_path {
+        let target_dir = cargo_target_dir(manifest_path)?;
+        find_built_library_in_workspace(&target_dir.join(args.artifact_dir()), package_name, triple)
+    } else if let Some(root) = &args.workspace_root {
+        find_built_library_in_workspace(&root.join(&profile_dir), package_name, triple)
+    } else if *in_horizon_root {
+        // Built library is in the workspace root's target dir
+        let workspace_target_dir = current_dir.join(&profile_dir);
+        find_built_library_in_workspace(&workspace_target_dir, package_name, triple)
+    } else {
+        // Check for workspace root in parent directories
+        let workspace_roots = find_workspace_roots(plugin_dir)?;
+        if workspace_roots.len() > 1 {
+            let candidates: Vec<String> = workspace_roots.iter().map(|r| r.display().to_string()).collect();
+            outln!("⚠️  Found nested workspace roots: {}", candidates.join(", "));
+            outln!("⚠️  Using the nearest one; pass --workspace-root to choose another");
+        }
+        let target_dir = if let Some(root) = workspace_roots.first() {
+            debugln!("Found workspace root: {}", root.display());
+            root.join(&profile_dir)
+        } else {
+            plugin_dir.join(&profile_dir)
+        };
+        find_built_library_in_workspace(&target_dir, package_name, triple)
+    }
+}
+
+/// Quiet period after a change before rebuilding, so saving several files triggers one build
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What wakes up the watch loop
+enum WatchEvent {
+    Files(notify::Result<notify::Event>),
+    Interrupted,
+}
+
+/// Build, then rebuild every time the plugin's sources or manifest change.
+/// Build failures are reported and the watch continues until Ctrl-C.
+fn watch_plugin(args: &BuildArgs) -> Result<()> {
+    let target = resolve_build_target(args)?;
+
+    let (tx, rx) = mpsc::channel();
+    let files_tx = tx.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = files_tx.send(WatchEvent::Files(event));
+    })
+    .context("Failed to start file watcher")?;
+    watcher.watch(&target.plugin_dir.join("src"), RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", target.plugin_dir.join("src").display()))?;
+    watcher.watch(&target.plugin_dir.join("Cargo.toml"), RecursiveMode::NonRecursive)?;
+
+    // This thread blocks on the channel, so the signal is caught on a runtime worker
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = tx.send(WatchEvent::Interrupted);
+        }
+    });
+
+    // Clearing only makes sense when someone is looking at a terminal
+    let term = Term::stdout();
+    let clear = args.clear && term.is_term();
+    let mut last_build: Option<(DateTime<Local>, bool)> = None;
+
+    if !clear {
+        show_watch_header(&target, None);
+    }
+    loop {
+        if clear {
+            term.clear_screen()?;
+            show_watch_header(&target, last_build);
+        } else if last_build.is_some() {
+            outln!();
+        }
+
+        let trigger = if last_build.is_some() { "Change detected, rebuilding" } else { "Building" };
+        statusln!("[{}] 🔨 {} {}...", Local::now().format("%H:%M:%S"), trigger, target.package_name);
+        let started = Instant::now();
+        let result = build_and_deploy(args, &target);
+        if args.notify {
+            notify_build_finished(&target.package_name, &result);
+        }
+        let elapsed = started.elapsed().as_secs_f64();
+        let finished = Local::now();
+        match &result {
+            Ok(()) => outln!("[{}] ✅ Build finished in {:.1}s", finished.format("%H:%M:%S"), elapsed),
+            Err(e) => outln!("[{}] {} {:#}", finished.format("%H:%M:%S"), format!("❌ Build failed after {:.1}s:", elapsed).red(), e),
+        }
+        last_build = Some((finished, result.is_ok()));
+
+        statusln!("🔍 Waiting for changes (Ctrl-C to stop)...");
+        if !wait_for_change(&rx)? {
+            outln!();
+            outln!("✅ Stopped watching {}", target.package_name);
+            return Ok(());
+        }
+    }
+}
+
+fn show_watch_header(target: &BuildTarget, last_build: Option<(DateTime<Local>, bool)>) {
+    outln!("🔄 Watching {} in {}", style(&target.package_name).cyan().bold(), target.plugin_dir.display());
+    if let Some((time, ok)) = last_build {
+        let result = if ok { "succeeded".green() } else { "failed".red() };
+        outln!("   Last build {} at {}", result, time.format("%H:%M:%S"));
+    }
+    outln!();
+}
+
+/// Block until a file is created, modified or removed, then until things go
+/// quiet. Returns false if the watch was interrupted instead.
+fn wait_for_change(rx: &mpsc::Receiver<WatchEvent>) -> Result<bool> {
+    loop {
+        match rx.recv().context("File watcher stopped")? {
+            WatchEvent::Interrupted => return Ok(false),
+            WatchEvent::Files(event) => {
+                let event = event?;
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                    break;
+                }
+            }
+        }
+    }
+    while let Ok(event) = rx.recv_timeout(WATCH_DEBOUNCE) {
+        if matches!(event, WatchEvent::Interrupted) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn list_plugins(args: ListArgs) -> Result<()> {
+    let horizon_root = args.path.unwrap_or_else(|| PathBuf::from("."));
+    let crates_dir = horizon_root.join("crates");
+    if !crates_dir.is_dir() {
+        return Err(anyhow!(
+            "No crates/ directory in {}; run this from the Horizon repo root or pass --path",
+            horizon_root.display()
+        ));
+    }
+
+    let target_dir = horizon_root.join("target/release");
+    let mut rows = Vec::new();
+    for plugin_dir in find_horizon_plugins(&crates_dir)? {
+        let manifest = plugin_dir.join("Cargo.toml");
+        let name = read_package_name(&manifest)?;
+        let version = read_package_version(&manifest)?;
+        let built = find_built_library_in_workspace(&target_dir, &name, HOST_TARGET)
+            .ok()
+            .and_then(|lib| lib.file_name().map(|f| f.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "no".to_string());
+        rows.push(vec![name, version, built]);
+    }
+
+    if rows.is_empty() {
+        outln!("📦 No plugin crates found in {}", crates_dir.display());
+        return Ok(());
+    }
+    outln!("📦 {} plugins in {}:", rows.len(), crates_dir.display());
+    outln!();
+    outln!("{}", render_table(&["Plugin", "Version", "Built"], &rows));
+    Ok(())
+}
+
+/// Plugin crates under `crates/`: `plugin_*` directories and crates that
+/// declare `[package.metadata.horizon]`, in name order
+fn find_horizon_plugins(crates_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut plugins = Vec::new();
+    for entry in fs::read_dir(crates_dir)? {
+        let path = entry?.path();
+        let manifest = path.join("Cargo.toml");
+        let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if !manifest.is_file() || dir_name == "plugin_system" {
+            continue;
+        }
+        if dir_name.starts_with("plugin_") || read_horizon_metadata(&manifest)?.is_some() {
+            plugins.push(path);
+        }
+    }
+    plugins.sort();
+    Ok(plugins)
+}
+
+/// `[package] version`, or "workspace" when it is inherited from the workspace
+fn read_package_version(cargo_toml_path: &Path) -> Result<String> {
+    let content = fs::read_to_string(cargo_toml_path)
+        .with_context(|| format!("Failed to read {}", cargo_toml_path.display()))?;
+    let doc = content.parse::<Document>()?;
+    let version = doc.get("package").and_then(|p| p.get("version"));
+    if let Some(version) = version.and_then(|v| v.as_str()) {
+        return Ok(version.to_string());
+    }
+    let inherited = version.and_then(|v| v.get("workspace")).and_then(|w| w.as_bool()) == Some(true);
+    Ok(if inherited { "workspace" } else { "-" }.to_string())
+}
+
+/// Read `[package] name`, telling a virtual workspace manifest apart from a broken crate manifest
+fn read_package_name(cargo_toml_path: &Path) -> Result<String> {
+    let content = fs::read_to_string(cargo_toml_path)
+        .with_context(|| format!("Failed to read {}", cargo_toml_path.display()))?;
+    let doc = content.parse::<Document>()?;
+
+    let Some(package) = doc.get("package").and_then(|t| t.as_table_like()) else {
+        if doc.contains_key("workspace") {
+            return Err(anyhow!(
+                "{} is a workspace manifest, not a plugin crate. Run `fbcli horizon plugin build <name>` from the \
+                 Horizon repo root, or run the build from inside the plugin's own directory.",
+                cargo_toml_path.display()
+            ));
+        }
+        return Err(anyhow!("Cargo.toml has no [package] table ({}).", cargo_toml_path.display()));
+    };
+
+    package.get("name")
+        .and_then(|n| n.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Cargo.toml missing [package] name field ({}).", cargo_toml_path.display()))
+}
+
+/// Find a plugin crate in Horizon's `crates/` dir. `chat` means `plugin_chat`,
+/// or failing that a `chat` crate that declares `[package.metadata.horizon]`.
+fn find_plugin_crate(crates_dir: &Path, plugin_arg: &str) -> Result<PathBuf> {
+    let crate_name = if plugin_arg.starts_with("plugin_") {
+        plugin_arg.to_string()
+    } else {
+        format!("plugin_{}", plugin_arg)
+    };
+    if crate_name == "plugin_system" {
+        return Err(anyhow!("plugin_system is not a buildable plugin crate"));
+    }
+    let plugin_path = crates_dir.join(&crate_name);
+    if plugin_path.exists() {
+        debugln!("Detected plugin crate by directory name: {}", crate_name);
+        return Ok(plugin_path);
+    }
+
+    let unprefixed_path = crates_dir.join(plugin_arg);
+    let manifest = unprefixed_path.join("Cargo.toml");
+    if plugin_arg != crate_name && manifest.exists() {
+        if read_horizon_metadata(&manifest)?.is_none() {
+            return Err(anyhow!(
+                "Crate '{}' is not a Horizon plugin: it has no plugin_ prefix and no [package.metadata.horizon] table",
+                plugin_arg
+            ));
+        }
+        debugln!("Detected plugin crate by [package.metadata.horizon]: {}", plugin_arg);
+        return Ok(unprefixed_path);
+    }
+
+    Err(anyhow!("Plugin crate '{}' not found in crates dir", crate_name))
+}
+
+/// Refuse a plugin name argument that names a different crate than the
+/// plugin directory the build was started from
+fn check_plugin_argument_matches(requested: &str, package_name: &str) -> Result<()> {
+    let requested_crate = if requested.starts_with("plugin_") {
+        requested.to_string()
+    } else {
+        format!("plugin_{}", requested)
+    };
+    // Crates identified by Horizon metadata may not carry the prefix
+    if requested_crate == package_name || requested == package_name {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Asked to build '{}' but the current directory is the plugin crate '{}'.\n\
+         Run without a plugin name to build '{}', or run from the Horizon repo root to build '{}'.",
+        requested, package_name, package_name, requested_crate
+    ))
+}
+
+/// Run `cargo build` for the plugin. With `stream`, cargo's output is printed
+/// as it arrives, with the progress bar suspended around each line; otherwise
+/// it is only shown when the build fails.
+fn build_package(target: &BuildTarget, build_args: &[&str], features: &[String], stream: Option<&ProgressBar>) -> Result<()> {
+    let mut command = Command::new("cargo");
+    command.arg("build")
+        .args(build_args)
+        .args(target.cargo_args())
+        .current_dir(&target.plugin_dir);
+    if !features.is_empty() {
+        command.arg("--features").arg(features.join(","));
+    }
+
+    let Some(pb) = stream else {
+        let output = command.output().context("Failed to execute cargo build")?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Cargo build failed:\n{}", error));
+        }
+        return Ok(());
+    };
+
+    // Cargo only colors a terminal, and its stderr is now a pipe
+    if std::io::stderr().is_terminal() && !ui::is_plain() {
+        command.arg("--color=always");
+    }
+    // Stdout may be reserved for --print-artifact-path; cargo reports progress on stderr
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute cargo build")?;
+    let mut captured = String::new();
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines() {
+            let line = line?;
+            pb.suspend(|| eprintln!("{}", line));
+            captured.push_str(&line);
+            captured.push('\n');
+        }
+    }
+    if !child.wait()?.success() {
+        return Err(anyhow!("Cargo build failed:\n{}", console::strip_ansi_codes(&captured)));
+    }
+    Ok(())
+}
+
+/// A stripped copy of the built library
+struct StrippedLibrary {
+    path: PathBuf,
+    before: u64,
+    after: u64,
+}
+
+/// Strip symbols from a copy of a built library in a `stripped/` directory next
+/// to it. Cargo's own output is never modified: cargo would consider a stripped
+/// artifact fresh, and later builds without `--strip` would deploy it.
+fn strip_library(lib_path: &Path) -> Result<StrippedLibrary> {
+    if cfg!(target_os = "windows") {
+        return Err(anyhow!("--strip is not supported on Windows; MSVC already keeps debug symbols in a separate .pdb"));
+    }
+    if !command_exists("strip") {
+        return Err(anyhow!("--strip needs the `strip` tool (binutils or the Xcode command line tools) in PATH"));
+    }
+
+    let (Some(lib_dir), Some(file_name)) = (lib_path.parent(), lib_path.file_name()) else {
+        return Err(anyhow!("Invalid library path: {}", lib_path.display()));
+    };
+    let stripped_dir = lib_dir.join("stripped");
+    fs::create_dir_all(&stripped_dir)
+        .with_context(|| format!("Failed to create {}", stripped_dir.display()))?;
+    let path = stripped_dir.join(file_name);
+    fs::copy(lib_path, &path).with_context(|| format!("Failed to copy the library to {}", path.display()))?;
+
+    let before = fs::metadata(lib_path)?.len();
+    // Keep the global symbols the Horizon server looks up when loading the plugin
+    let flag = if cfg!(target_os = "macos") { "-x" } else { "--strip-unneeded" };
+    let output = Command::new("strip")
+        .arg(flag)
+        .arg(&path)
+        .output()
+        .context("Failed to run strip")?;
+    if !output.status.success() {
+        return Err(anyhow!("strip failed on {}:\n{}", path.display(), String::from_utf8_lossy(&output.stderr)));
+    }
+    let after = fs::metadata(&path)?.len();
+    Ok(StrippedLibrary { path, before, after })
+}
+
+/// Make sure the process given to --signal-pid exists and can be signaled
+fn check_process_running(pid: u32) -> Result<()> {
+    if cfg!(target_os = "windows") {
+        // Nothing will be sent; signal_process warns about it after the copy
+        return Ok(());
+    }
+    let output = Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .context("Failed to run kill")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "No running process with pid {} (or it belongs to another user); check --signal-pid",
+            pid
+        ));
+    }
+    Ok(())
+}
+
+/// Send `signal` to `pid`. Returns false when signals aren't available (Windows).
+fn signal_process(pid: u32, signal: &str) -> Result<bool> {
+    if cfg!(target_os = "windows") {
+        outln!("⚠️  --signal-pid is not supported on Windows; restart or reload the server yourself");
+        return Ok(false);
+    }
+    let output = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg(pid.to_string())
+        .output()
+        .context("Failed to run kill")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to send SIG{} to process {}:\n{}",
+            signal,
+            pid,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(true)
+}
+
+/// Longest a plugin may take to load and construct during `--runtime-check`
+const RUNTIME_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Entry point exported by `create_simple_plugin!`
+const PLUGIN_ENTRY_SYMBOL: &[u8] = b"create_plugin";
+
+/// Load the plugin in a child fbcli process so a crash or hang can't take down the build
+fn runtime_check(lib_path: &Path) -> Result<()> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["horizon", "plugin", "__runtime-check"])
+        .arg(lib_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to start the runtime check process")?;
+
+    // Drain stderr as it's written: a plugin that logs more than the pipe buffer
+    // while loading would otherwise block forever and look like a timeout
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = std::thread::spawn(move || {
+        let mut stderr = Vec::new();
+        let _ = std::io::Read::read_to_end(&mut stderr_pipe, &mut stderr);
+        stderr
+    });
+
+    let deadline = Instant::now() + RUNTIME_CHECK_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!(
+                "Runtime check timed out: {} did not finish loading within {}s",
+                lib_path.display(),
+                RUNTIME_CHECK_TIMEOUT.as_secs()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = stderr_reader.join().unwrap_or_default();
+    // Keep the panic message, not the backtrace that a non-unwinding panic forces
+    let stderr: Vec<_> = String::from_utf8_lossy(&stderr)
+        .lines()
+        .take_while(|line| !line.starts_with("stack backtrace:"))
+        .map(str::to_string)
+        .collect();
+    let reason = match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        None => "crashed (killed by a signal, e.g. a panic across the plugin boundary)".to_string(),
+    };
+    Err(anyhow!("Runtime check failed: loading {} {}\n{}", lib_path.display(), reason, stderr.join("\n").trim()))
+}
+
+/// Raw `*mut dyn Plugin` as returned by the plugin's entry point
+#[repr(C)]
+struct RawPluginPtr {
+    data: *mut std::ffi::c_void,
+    vtable: *mut std::ffi::c_void,
+}
+
+/// Child side of `--runtime-check`: load the library and call its entry point
+fn runtime_check_host(library: &Path) -> Result<()> {
+    // SAFETY: this runs in a throwaway process whose only job is to run the
+    // plugin's initialisation code; the parent treats any crash as a failure.
+    unsafe {
+        let lib = libloading::Library::new(library)
+            .with_context(|| format!("Failed to load {}", library.display()))?;
+        let create: libloading::Symbol<unsafe extern "C" fn() -> RawPluginPtr> = lib.get(PLUGIN_ENTRY_SYMBOL)
+            .with_context(|| format!("{} does not export create_plugin", library.display()))?;
+        let plugin = create();
+        if plugin.data.is_null() || plugin.vtable.is_null() {
+            return Err(anyhow!("create_plugin returned a null plugin"));
+        }
+        // Never unload: the plugin's code must stay mapped while the instance exists
+        std::mem::forget(lib);
+    }
+    Ok(())
+}
+
+/// What `clean_package` ended up removing
+enum CleanScope {
+    /// Just the plugin package's artifacts
+    Package,
+    /// Everything for the profile, after the package-scoped clean failed
+    Everything,
+}
+
+/// `cargo clean -p <package>` for the selected profile, falling back to a full
+/// `cargo clean` of the profile when cargo can't clean just the package
+fn clean_package(target: &BuildTarget, build_args: &[&str]) -> Result<CleanScope> {
+    let package_clean = {
+        let mut command = Command::new("cargo");
+        command.arg("clean").args(build_args);
+        if target.manifest_path.is_some() {
+            command.args(target.cargo_args());
+        } else {
+            command.args(["-p", &target.package_name]);
+        }
+        run_cargo_clean(command, target)
+    };
+    let Err(package_error) = package_clean else {
+        return Ok(CleanScope::Package);
+    };
+
+    let mut command = Command::new("cargo");
+    command.arg("clean").args(build_args);
+    if let Some(manifest) = &target.manifest_path {
+        command.arg("--manifest-path").arg(manifest);
+    }
+    run_cargo_clean(command, target)
+        .map(|()| CleanScope::Everything)
+        .with_context(|| format!("{:#}", package_error))
+}
+
+fn run_cargo_clean(mut command: Command, target: &BuildTarget) -> Result<()> {
+    let output = command
+        .current_dir(&target.plugin_dir)
+        .output()
+        .context("Failed to execute cargo clean")?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Cargo clean failed:\n{}", error));
+    }
+
+    Ok(())
+}
+
+/// The target directory cargo uses for a manifest's workspace, as `cargo metadata`
+/// reports it (so `CARGO_TARGET_DIR` and `.cargo/config.toml` are honored)
+fn cargo_target_dir(manifest_path: &Path) -> Result<PathBuf> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps", "--manifest-path"])
+        .arg(manifest_path)
+        .output()
+        .context("Failed to execute cargo metadata")?;
+    if !output.status.success() {
+        return Err(anyhow!("cargo metadata failed:\n{}", String::from_utf8_lossy(&output.stderr)));
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .context("Failed to parse cargo metadata output")?;
+    metadata["target_directory"].as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("cargo metadata did not report a target directory"))
+}
+
+/// Ancestors of `plugin_dir` whose Cargo.toml declares a `[workspace]`, nearest first
+fn find_workspace_roots(plugin_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut roots = Vec::new();
+    for parent in plugin_dir.ancestors().skip(1) {
+        let candidate = parent.join("Cargo.toml");
+        if candidate.exists() {
+            let content = fs::read_to_string(&candidate)?;
+            if content.contains("[workspace]") {
+                roots.push(parent.to_path_buf());
+            }
+        }
+    }
+    Ok(roots)
+}
+
+/// Find the plugin's library among the artifacts for `target_triple` in `target_dir`
+fn find_built_library_in_workspace(target_dir: &Path, plugin_name: &str, target_triple: &str) -> Result<PathBuf> {
+    if !target_dir.exists() {
+        return Err(anyhow!("Target directory not found for plugin {} ({}).", plugin_name, target_dir.display()));
+    }
+    let library_extension = library_extension(target_triple);
+    // Sorted so the same library wins every time when several match equally well
+    let mut best: Option<(u8, PathBuf)> = None;
+    for entry in WalkDir::new(target_dir).max_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        if let Some(extension) = path.extension() {
+            let extension = extension.to_string_lossy();
+            if extension.eq_ignore_ascii_case(library_extension) {
+                if let Some(file_name) = path.file_name() {
+                    let rank = library_match_rank(&file_name.to_string_lossy(), plugin_name);
+                    if rank > best.as_ref().map_or(0, |(best_rank, _)| *best_rank) {
+                        best = Some((rank, path.to_path_buf()));
+                    }
+                }
+            }
+        }
+    }
+    best.map(|(_, path)| path)
+        .ok_or_else(|| anyhow!("Could not find built plugin library in {} for plugin {}", target_dir.display(), plugin_name))
+}
+
+/// Extension of a dynamic library built for a target triple
+fn library_extension(target_triple: &str) -> &'static str {
+    if target_triple.contains("windows") {
+        "dll"
+    } else if target_triple.contains("apple") {
+        "dylib"
+    } else {
+        "so"
+    }
+}
+
+/// How well a library file name matches a package: 2 for the name cargo gives
+/// the library (`libplugin_chat.so`, `plugin_chat.dll`), 1 for a looser prefix
+/// match, 0 for none. Names are compared case-insensitively, since the
+/// filesystems on macOS and Windows are case-insensitive by default.
+fn library_match_rank(file_name: &str, package_name: &str) -> u8 {
+    let name = file_name.to_lowercase();
+    let crate_name = package_name.to_lowercase().replace('-', "_");
+    let stem = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem);
+    if stem == crate_name || stem.strip_prefix("lib") == Some(crate_name.as_str()) {
+        2
+    } else if name.starts_with(&crate_name) || (name.starts_with("plugin_") && name.contains(&crate_name)) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Remove copies of `target_path` whose names differ only in case, left by an
+/// earlier deploy, so the plugins directory ends up with exactly one. On a
+/// case-insensitive filesystem there are none: they are the same file.
+fn remove_case_variants(target_path: &Path) -> Result<()> {
+    let (Some(dir), Some(name)) = (target_path.parent(), target_path.file_name()) else {
+        return Ok(());
+    };
+    let name = name.to_string_lossy();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let other = entry.file_name().to_string_lossy().into_owned();
+        if other != name && other.to_lowercase() == name.to_lowercase() {
+            fs::remove_file(entry.path())
+                .with_context(|| format!("Failed to remove stale copy {}", entry.path().display()))?;
+            outln!("🔧 Removed stale copy {} (differs only in case)", entry.path().display());
+        }
+    }
+    Ok(())
+}
+
+/// Copy the library into `<horizon>/plugins`, optionally under a different
+/// file name, and return the destination path
+fn copy_to_horizon_plugins(
+    lib_path: &Path,
+    horizon_path: &Path,
+    file_name: Option<&str>,
+    wait_for_lock: Option<u64>,
+) -> Result<PathBuf> {
+    let plugins_dir = horizon_path.join("plugins");
+    
+    // Create plugins directory if it doesn't exist
+    if !plugins_dir.exists() {
+        fs::create_dir_all(&plugins_dir)
+            .with_context(|| format!("Failed to create plugins directory: {}", plugins_dir.display()))?;
+    }
+
+    let file_name = match file_name {
+        Some(name) => name.as_ref(),
+        None => lib_path.file_name().ok_or_else(|| anyhow!("Invalid library file path"))?,
+    };
+    
+    let target_path = plugins_dir.join(file_name);
+
+    // Stage the copy next to the destination so a server watching the plugins
+    // directory never sees a half-written library, then rename it into place
+    let mut staging_name = std::ffi::OsString::from(".");
+    staging_name.push(file_name);
+    staging_name.push(".fbcli-tmp");
+    let staging_path = plugins_dir.join(staging_name);
+    copy_plugin_file(lib_path, &staging_path)?;
+    if let Err(e) = verify_copy(lib_path, &staging_path) {
+        let _ = fs::remove_file(&staging_path);
+        return Err(e);
+    }
+
+    let deadline = wait_for_lock.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let mut delay = Duration::from_millis(250);
+    let mut warned = false;
+
+    loop {
+        match fs::rename(&staging_path, &target_path) {
+            Ok(()) => {
+                remove_case_variants(&target_path)?;
+                return Ok(target_path);
+            }
+            Err(e) if is_file_locked(&e) => {
+                let Some(deadline) = deadline.filter(|d| Instant::now() < *d) else {
+                    let _ = fs::remove_file(&staging_path);
+                    return Err(anyhow!(
+                        "Failed to copy plugin to {}: the file is locked. The Horizon server may still have the plugin loaded; stop it or pass --wait-for-lock <secs>",
+                        target_path.display()
+                    ));
+                };
+                if !warned {
+                    outln!(
+                        "{} {} is locked; the Horizon server may still have the plugin open. Retrying...",
+                        "⏳".yellow(),
+                        target_path.display()
+                    );
+                    warned = true;
+                }
+                std::thread::sleep(delay.min(deadline.saturating_duration_since(Instant::now())));
+                delay = (delay * 2).min(Duration::from_secs(4));
+            }
+            Err(e) => {
+                // e.g. the plugins directory is a mount that doesn't support rename
+                let _ = fs::remove_file(&staging_path);
+                outln!("⚠️  Could not move the plugin into place atomically ({}); copying in place instead", e);
+                copy_plugin_file(lib_path, &target_path)?;
+                verify_copy(lib_path, &target_path)?;
+                remove_case_variants(&target_path)?;
+                return Ok(target_path);
+            }
+        }
+    }
+}
+
+/// `fs::copy` with a clear message, instead of the raw OS error, when the destination is full
+fn copy_plugin_file(source: &Path, destination: &Path) -> Result<()> {
+    match fs::copy(source, destination) {
+        Ok(_) => Ok(()),
+        Err(e) if is_out_of_space(&e) => {
+            // Don't leave a truncated library taking up what little space is left
+            let _ = fs::remove_file(destination);
+            let size = fs::metadata(source).map(|m| format_bytes(m.len())).unwrap_or_else(|_| "?".to_string());
+            Err(anyhow!(
+                "Not enough disk space to copy the plugin ({}) into {}; free up space on the filesystem at {}",
+                size,
+                destination.parent().unwrap_or(destination).display(),
+                filesystem_root(destination).display()
+            ))
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to copy plugin to {}", destination.display())),
+    }
+}
+
+/// Whether an I/O error means the destination filesystem (or the user's quota) is full
+fn is_out_of_space(error: &std::io::Error) -> bool {
+    matches!(error.kind(), std::io::ErrorKind::StorageFull | std::io::ErrorKind::QuotaExceeded)
+}
+
+/// Mount point (Unix) or drive (Windows) that holds `path`, for error messages
+fn filesystem_root(path: &Path) -> PathBuf {
+    let dir = path.ancestors().skip(1).find(|dir| dir.exists()).unwrap_or(path);
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // Walk up while the parent is still on the same device
+        let Ok(device) = fs::metadata(&dir).map(|m| m.dev()) else {
+            return dir;
+        };
+        let mut root = dir.as_path();
+        while let Some(parent) = root.parent() {
+            if fs::metadata(parent).map(|m| m.dev()).ok() != Some(device) {
+                break;
+            }
+            root = parent;
+        }
+        root.to_path_buf()
+    }
+    #[cfg(not(unix))]
+    {
+        dir.ancestors().last().map(Path::to_path_buf).unwrap_or(dir)
+    }
+}
+
+/// Make sure the deployed file is byte-for-byte the built library; `fs::copy`
+/// can report success after a short write (e.g. disk full)
+fn verify_copy(source: &Path, destination: &Path) -> Result<()> {
+    let source_len = fs::metadata(source)?.len();
+    let destination_len = fs::metadata(destination)?.len();
+    if source_len != destination_len {
+        return Err(anyhow!(
+            "Copied plugin at {} is {} bytes but the built library is {} bytes; the copy is incomplete",
+            destination.display(), destination_len, source_len
+        ));
+    }
+
+    let source_hash = sha256_file(source)?;
+    let destination_hash = sha256_file(destination)?;
+    if source_hash != destination_hash {
+        return Err(anyhow!(
+            "Copied plugin at {} does not match the built library (SHA-256 {} vs {})",
+            destination.display(), destination_hash, source_hash
+        ));
+    }
+    Ok(())
+}
+
+/// Fields read from a plugin's `[package.metadata.horizon]` table
+#[derive(Debug, Default)]
+struct HorizonMetadata {
+    plugin_id: Option<String>,
+    display_name: Option<String>,
+    /// Raw `features` value, checked only by the builds that use it so one
+    /// malformed crate can't break plugin discovery for the whole workspace
+    features: Option<Item>,
+}
+
+/// Read `[package.metadata.horizon]` from a manifest, if the table is present
+fn read_horizon_metadata(manifest_path: &Path) -> Result<Option<HorizonMetadata>> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let doc = content.parse::<Document>()?;
+
+    let Some(table) = doc.get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("horizon"))
+        .and_then(|h| h.as_table_like())
+    else {
+        return Ok(None);
+    };
+
+    let string_field = |key: &str| table.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    Ok(Some(HorizonMetadata {
+        plugin_id: string_field("plugin_id"),
+        display_name: string_field("display_name"),
+        features: table.get("features").cloned(),
+    }))
+}
+
+/// Cargo features a deployment build enables, from `features` in the plugin's Horizon metadata
+fn features_from_metadata(manifest_path: &Path) -> Result<Vec<String>> {
+    let features = read_horizon_metadata(manifest_path)?
+        .and_then(|metadata| metadata.features)
+        .ok_or_else(|| anyhow!(
+            "--features-from-metadata requires a `features` array in [package.metadata.horizon] of {}",
+            manifest_path.display()
+        ))?;
+
+    let invalid = || anyhow!(
+        "`features` in [package.metadata.horizon] of {} must be an array of feature names",
+        manifest_path.display()
+    );
+    let array = features.as_array().ok_or_else(invalid)?;
+    array.iter()
+        .map(|feature| feature.as_str().map(str::to_string).ok_or_else(invalid))
+        .collect()
+}
+
+/// Deployed file name taken from the plugin's Horizon metadata, keeping the
+/// library's platform extension
+fn deploy_name_from_metadata(plugin_dir: &Path, lib_path: &Path) -> Result<String> {
+    let manifest_path = plugin_dir.join("Cargo.toml");
+    let metadata = read_horizon_metadata(&manifest_path)?.ok_or_else(|| anyhow!(
+        "--name-from-metadata requires a [package.metadata.horizon] table in {}",
+        manifest_path.display()
+    ))?;
+    let name = metadata.plugin_id.or(metadata.display_name).ok_or_else(|| anyhow!(
+        "[package.metadata.horizon] in {} has neither plugin_id nor display_name",
+        manifest_path.display()
+    ))?;
+
+    validate_deploy_name(&name)?;
+
+    match lib_path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => Ok(format!("{}.{}", name, extension)),
+        None => Ok(name),
+    }
+}
+
+/// Ensure a metadata-provided name is safe to use as a file name on every platform
+fn validate_deploy_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(anyhow!("Plugin name from metadata is empty"));
+    }
+    if name.starts_with('.') {
+        return Err(anyhow!("Plugin name from metadata '{}' cannot start with '.'", name));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err(anyhow!(
+            "Plugin name from metadata '{}' can only contain ASCII letters, digits, '_', '-' and '.'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Debug symbol files or bundles that the toolchain places next to a library
+fn find_debug_symbols(lib_path: &Path) -> Vec<PathBuf> {
+    let Some(file_name) = lib_path.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    let candidates = [
+        // Windows: plugin_foo.dll -> plugin_foo.pdb
+        lib_path.with_extension("pdb"),
+        // macOS: libplugin_foo.dylib -> libplugin_foo.dylib.dSYM/
+        lib_path.with_file_name(format!("{}.dSYM", file_name)),
+        // Linux with split-debuginfo=packed: libplugin_foo.so -> libplugin_foo.so.dwp
+        lib_path.with_file_name(format!("{}.dwp", file_name)),
+    ];
+    candidates.into_iter().filter(|path| path.exists()).collect()
+}
+
+/// Copy the library's companion debug symbols into the plugins directory
+fn copy_debug_symbols(lib_path: &Path, plugins_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut copied = Vec::new();
+    for symbols in find_debug_symbols(lib_path) {
+        let file_name = symbols.file_name()
+            .ok_or_else(|| anyhow!("Invalid debug symbols path"))?;
+        let destination = plugins_dir.join(file_name);
+        copy_recursive(&symbols, &destination)
+            .with_context(|| format!("Failed to copy debug symbols to {}", destination.display()))?;
+        copied.push(destination);
+    }
+    Ok(copied)
+}
+
+/// Copy a file, or a directory tree such as a .dSYM bundle
+fn copy_recursive(source: &Path, destination: &Path) -> Result<()> {
+    if source.is_file() {
+        fs::copy(source, destination)?;
+        return Ok(());
+    }
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        let target = destination.join(entry.path().strip_prefix(source)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a copy failed because another process holds the destination open
+fn is_file_locked(error: &std::io::Error) -> bool {
+    if cfg!(windows) {
+        // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        matches!(error.raw_os_error(), Some(5) | Some(32) | Some(33))
+    } else {
+        error.kind() == std::io::ErrorKind::ExecutableFileBusy
+    }
+}
+
+/// A problem found by `plugin doctor`, with the repair to apply if one is known
+#[derive(Debug)]
+struct DoctorIssue {
+    description: String,
+    fix: Option<DoctorFix>,
+}
+
+#[derive(Debug, PartialEq)]
+enum DoctorFix {
+    /// Add "cdylib" to `[lib] crate-type`
+    CrateType,
+    /// Add `horizon_event_system` to `[dependencies]` at this version
+    EventSystemDependency(String),
+    /// Rename the package to this name
+    PackageName(String),
+}
+
+impl DoctorFix {
+    fn describe(&self) -> String {
+        match self {
+            DoctorFix::CrateType => "added \"cdylib\" to [lib] crate-type".to_string(),
+            DoctorFix::EventSystemDependency(version) => {
+                format!("added horizon_event_system = \"{}\" to [dependencies]", version)
+            }
+            DoctorFix::PackageName(name) => format!("renamed package to {}", name),
+        }
+    }
+}
+
+fn plugin_doctor(args: DoctorArgs) -> Result<()> {
+    let plugin_dir = args.path.unwrap_or_else(|| PathBuf::from("."));
+    let manifest_path = plugin_dir.join("Cargo.toml");
+    let content = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let mut doc = content.parse::<Document>()?;
+
+    outln!("🔍 Checking plugin crate at {}", style(plugin_dir.display()).yellow());
+    outln!();
+
+    let horizon_path = args.horizon_path.unwrap_or_else(|| PathBuf::from("../Horizon"));
+    let event_system_version = horizon_event_system_version(&horizon_path);
+    let issues = diagnose_plugin_manifest(&doc, event_system_version.as_deref());
+
+    if issues.is_empty() {
+        outln!("{}", "✅ No issues found".green().bold());
+        return Ok(());
+    }
+
+    let mut remaining = 0;
+    let mut fixed = 0;
+    for issue in &issues {
+        match (&issue.fix, args.fix) {
+            (Some(fix), true) => {
+                apply_doctor_fix(&mut doc, fix)?;
+                outln!("  🔧 {}: {}", issue.description, fix.describe());
+                fixed += 1;
+            }
+            (Some(_), false) => {
+                outln!("  ⚠️  {} (fixable with --fix)", issue.description);
+                remaining += 1;
+            }
+            (None, _) => {
+                outln!("  {} {}", "❌".red(), issue.description);
+                remaining += 1;
+            }
+        }
+    }
+
+    if fixed > 0 {
+        fs::write(&manifest_path, doc.to_string())?;
+        outln!();
+        outln!("💾 Applied {} fix(es) to {}", fixed, manifest_path.display());
+    }
+
+    if remaining > 0 {
+        return Err(anyhow!("{} issue(s) remaining in {}", remaining, manifest_path.display()));
+    }
+    Ok(())
+}
+
+/// Version of horizon_event_system in a Horizon checkout, if one is present
+fn horizon_event_system_version(horizon_path: &Path) -> Option<String> {
+    let manifest = horizon_path.join("crates/horizon_event_system/Cargo.toml");
+    let doc = fs::read_to_string(manifest).ok()?.parse::<Document>().ok()?;
+    let version = doc.get("package")?.get("version")?;
+    if let Some(version) = version.as_str() {
+        return Some(version.to_string());
+    }
+
+    // `version.workspace = true`: read it from the Horizon workspace manifest
+    let workspace = fs::read_to_string(horizon_path.join("Cargo.toml")).ok()?.parse::<Document>().ok()?;
+    workspace.get("workspace")?.get("package")?.get("version")?.as_str().map(str::to_string)
+}
+
+fn diagnose_plugin_manifest(doc: &Document, event_system_version: Option<&str>) -> Vec<DoctorIssue> {
+    let mut issues = Vec::new();
+
+    // Crates with [package.metadata.horizon] are found by that table and may
+    // be named freely, so renaming them to plugin_* would undo their naming
+    let has_horizon_metadata = doc.get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("horizon"))
+        .is_some_and(|h| h.is_table_like());
+    match doc.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()) {
+        Some(_) if has_horizon_metadata => {}
+        Some(name) => {
+            let normalized = normalize_plugin_package_name(name);
+            if normalized != name {
+                issues.push(DoctorIssue {
+                    description: format!("Package name '{}' should be '{}'", name, normalized),
+                    fix: Some(DoctorFix::PackageName(normalized)),
+                });
+            }
+        }
+        None => issues.push(DoctorIssue {
+            description: "Cargo.toml is missing [package] name".to_string(),
+            fix: None,
+        }),
+    }
+
+    let has_cdylib = doc.get("lib")
+        .and_then(|lib| lib.get("crate-type"))
+        .and_then(|types| types.as_array())
+        .is_some_and(|types| types.iter().any(|t| t.as_str() == Some("cdylib")));
+    if !has_cdylib {
+        issues.push(DoctorIssue {
+            description: "[lib] crate-type does not include \"cdylib\"; Horizon cannot load the plugin".to_string(),
+            fix: Some(DoctorFix::CrateType),
+        });
+    }
+
+    let has_event_system = doc.get("dependencies")
+        .and_then(|deps| deps.as_table_like())
+        .is_some_and(|deps| deps.contains_key("horizon_event_system"));
+    if !has_event_system {
+        issues.push(match event_system_version {
+            Some(version) => DoctorIssue {
+                description: "Missing horizon_event_system dependency".to_string(),
+                fix: Some(DoctorFix::EventSystemDependency(version.to_string())),
+            },
+            None => DoctorIssue {
+                description: "Missing horizon_event_system dependency (no Horizon checkout found to \
+                    pick a version; pass --horizon-path)".to_string(),
+                fix: None,
+            },
+        });
+    }
+
+    issues
+}
+
+/// `plugin_` prefixed, underscore-separated package name
+fn normalize_plugin_package_name(name: &str) -> String {
+    let name = name.replace('-', "_");
+    if name.starts_with("plugin_") {
+        name
+    } else {
+        format!("plugin_{}", name)
+    }
+}
+
+fn apply_doctor_fix(doc: &mut Document, fix: &DoctorFix) -> Result<()> {
+    match fix {
+        DoctorFix::CrateType => {
+            let lib = doc.entry("lib")
+                .or_insert(toml_edit::table())
+                .as_table_like_mut()
+                .ok_or_else(|| anyhow!("[lib] is not a table"))?;
+            let crate_types = lib.entry("crate-type")
+                .or_insert(value(toml_edit::Array::new()))
+                .as_array_mut()
+                .ok_or_else(|| anyhow!("[lib] crate-type is not an array"))?;
+            crate_types.push("cdylib");
+        }
+        DoctorFix::EventSystemDependency(version) => {
+            doc.entry("dependencies")
+                .or_insert(toml_edit::table())
+                .as_table_like_mut()
+                .ok_or_else(|| anyhow!("[dependencies] is not a table"))?
+                .insert("horizon_event_system", value(version.as_str()));
+        }
+        DoctorFix::PackageName(name) => {
+            doc["package"]["name"] = value(name.as_str());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horizon_workspace(members: &str) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("Cargo.toml"),
+            format!("[workspace]\nmembers = [{}]\n", members),
+        ).unwrap();
+        fs::create_dir_all(root.path().join("crates")).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_workspace_collision_with_member() {
+        let root = horizon_workspace(r#""crates/plugin_greeter""#);
+        let crates = root.path().join("crates");
+
+        assert!(check_workspace_collision(&crates, "greeter").is_err());
+        assert!(check_workspace_collision(&crates, "plugin_greeter").is_err());
+        assert!(check_workspace_collision(&crates, "chat").is_ok());
+    }
+
+    #[test]
+    fn test_workspace_collision_with_directory() {
+        let root = horizon_workspace(r#""crates/*""#);
+        let crates = root.path().join("crates");
+        fs::create_dir_all(crates.join("plugin_chat")).unwrap();
+
+        assert!(check_workspace_collision(&crates, "chat").is_err());
+        assert!(check_workspace_collision(&crates, "greeter").is_ok());
+    }
+
+    #[test]
+    fn test_find_plugin_crate_by_metadata() {
+        let root = horizon_workspace(r#""crates/*""#);
+        let crates = root.path().join("crates");
+        fs::create_dir_all(crates.join("plugin_chat")).unwrap();
+        fs::create_dir_all(crates.join("inventory")).unwrap();
+        fs::write(
+            crates.join("inventory/Cargo.toml"),
+            "[package]\nname = \"inventory\"\n\n[package.metadata.horizon]\nplugin_id = \"inventory\"\n",
+        ).unwrap();
+        fs::create_dir_all(crates.join("shared")).unwrap();
+        fs::write(crates.join("shared/Cargo.toml"), "[package]\nname = \"shared\"\n").unwrap();
+
+        assert_eq!(find_plugin_crate(&crates, "chat").unwrap(), crates.join("plugin_chat"));
+        assert_eq!(find_plugin_crate(&crates, "inventory").unwrap(), crates.join("inventory"));
+        let err = find_plugin_crate(&crates, "shared").unwrap_err();
+        assert!(err.to_string().contains("not a Horizon plugin"));
+        assert!(find_plugin_crate(&crates, "missing").is_err());
+        assert!(find_plugin_crate(&crates, "system").is_err());
+
+        assert!(check_plugin_argument_matches("inventory", "inventory").is_ok());
+    }
+
+    #[test]
+    fn test_deploy_name_from_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("target/release/libplugin_chat.so");
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"plugin_chat\"\n\n[package.metadata.horizon]\ndisplay_name = \"Chat\"\nplugin_id = \"chat-v2\"\n",
+        ).unwrap();
+        assert_eq!(deploy_name_from_metadata(dir.path(), &lib).unwrap(), "chat-v2.so");
+
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"plugin_chat\"\n\n[package.metadata.horizon]\ndisplay_name = \"Chat Log\"\n",
+        ).unwrap();
+        assert!(deploy_name_from_metadata(dir.path(), &lib).is_err());
+
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"plugin_chat\"\n").unwrap();
+        assert!(deploy_name_from_metadata(dir.path(), &lib).is_err());
+    }
+
+    #[test]
+    fn test_features_from_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[package]\nname = \"plugin_chat\"\n\n[package.metadata.horizon]\nfeatures = [\"persistence\", \"metrics\"]\n",
+        ).unwrap();
+        assert_eq!(features_from_metadata(&manifest).unwrap(), ["persistence", "metrics"]);
+
+        fs::write(&manifest, "[package]\nname = \"plugin_chat\"\n\n[package.metadata.horizon]\nplugin_id = \"chat\"\nfeatures = \"metrics\"\n").unwrap();
+        assert!(features_from_metadata(&manifest).is_err());
+        // Only the features build rejects it; discovery still reads the rest of the table
+        let metadata = read_horizon_metadata(&manifest).unwrap().unwrap();
+        assert_eq!(metadata.plugin_id.as_deref(), Some("chat"));
+
+        fs::write(&manifest, "[package]\nname = \"plugin_chat\"\n\n[package.metadata.horizon]\nplugin_id = \"chat\"\n").unwrap();
+        assert!(features_from_metadata(&manifest).is_err());
+    }
+
+    #[test]
+    fn test_validate_deploy_name() {
+        assert!(validate_deploy_name("chat").is_ok());
+        assert!(validate_deploy_name("chat-v2.1").is_ok());
+        assert!(validate_deploy_name("").is_err());
+        assert!(validate_deploy_name(".hidden").is_err());
+        assert!(validate_deploy_name("../escape").is_err());
+        assert!(validate_deploy_name("with space").is_err());
+    }
+
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("my-http-plugin"), "MyHttpPlugin");
+        assert_eq!(to_pascal_case("foo_bar_baz"), "FooBarBaz");
+        assert_eq!(to_pascal_case("XMLParser"), "XMLParser");
+        assert_eq!(to_pascal_case("myPlugin"), "MyPlugin");
+        assert_eq!(to_pascal_case("HTTP"), "Http");
+        assert_eq!(to_pascal_case("xml_HTTPServer"), "XmlHTTPServer");
+    }
+
+    #[test]
+    fn test_struct_name_override() {
+        assert_eq!(default_struct_name("http"), "HttpPlugin");
+        assert_eq!(parse_struct_name("HTTPPlugin").unwrap(), "HTTPPlugin");
+        assert_eq!(parse_struct_name("_Raw2").unwrap(), "_Raw2");
+        assert!(parse_struct_name("2Fast").is_err());
+        assert!(parse_struct_name("My-Plugin").is_err());
+        assert!(parse_struct_name("Self").is_err());
+        assert!(parse_struct_name("_").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("http");
+        write_embedded_template(&plugin_dir).unwrap();
+        update_plugin_code(&plugin_dir, "http", "HTTPPlugin", true).unwrap();
+        let lib = fs::read_to_string(plugin_dir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub struct HTTPPlugin {"));
+        assert!(lib.contains("create_simple_plugin!(HTTPPlugin);"));
+        assert!(lib.contains("let plugin = HTTPPlugin::new();"));
+        assert!(!lib.contains("HttpPlugin"));
+        assert_eq!(exported_struct_name(&plugin_dir).as_deref(), Some("HTTPPlugin"));
+    }
+
+    #[test]
+    fn test_apply_async_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_path = dir.path().join("Cargo.toml");
+        fs::write(&cargo_path, "[package]\nname = \"plugin_chat\"\n\n[dependencies]\nserde = \"1.0\"\n").unwrap();
+
+        apply_async_runtime(dir.path(), AsyncRuntime::Tokio).unwrap();
+        let doc = fs::read_to_string(&cargo_path).unwrap().parse::<Document>().unwrap();
+        assert_eq!(doc["dev-dependencies"]["tokio"]["version"].as_str(), Some("1.0"));
+        assert!(doc["dependencies"].get("tokio").is_none());
+        assert_eq!(doc["dependencies"]["serde"].as_str(), Some("1.0"));
+
+        apply_async_runtime(dir.path(), AsyncRuntime::None).unwrap();
+        let doc = fs::read_to_string(&cargo_path).unwrap().parse::<Document>().unwrap();
+        assert!(doc["dev-dependencies"].get("tokio").is_none());
+        assert_eq!(doc["dependencies"]["serde"].as_str(), Some("1.0"));
+
+        // A template that really uses tokio keeps it either way
+        fs::write(&cargo_path, "[package]\nname = \"plugin_chat\"\n\n[dependencies]\ntokio = \"1\"\n").unwrap();
+        apply_async_runtime(dir.path(), AsyncRuntime::Tokio).unwrap();
+        apply_async_runtime(dir.path(), AsyncRuntime::None).unwrap();
+        let doc = fs::read_to_string(&cargo_path).unwrap().parse::<Document>().unwrap();
+        assert_eq!(doc["dependencies"]["tokio"].as_str(), Some("1"));
+        assert!(doc.get("dev-dependencies").and_then(|deps| deps.get("tokio")).is_none());
+    }
+
+    #[test]
+    fn test_fill_readme_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "# {{plugin_name}}\n`{{crate_name}}` exports `{{struct_name}}`.\n",
+        ).unwrap();
+
+        fill_readme_placeholders(dir.path(), "chat_log", &default_struct_name("chat_log")).unwrap();
+
+        let readme = fs::read_to_string(dir.path().join("README.md")).unwrap();
+        assert_eq!(readme, "# chat_log\n`plugin_chat_log` exports `ChatLogPlugin`.\n");
+    }
+
+    #[test]
+    fn test_find_and_copy_debug_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let release = dir.path().join("target/release");
+        fs::create_dir_all(release.join("libplugin_chat.dylib.dSYM/Contents/Resources")).unwrap();
+        fs::write(release.join("libplugin_chat.dylib.dSYM/Contents/Resources/dwarf"), "dwarf").unwrap();
+        fs::write(release.join("plugin_chat.pdb"), "pdb").unwrap();
+
+        assert!(find_debug_symbols(&release.join("libplugin_greeter.so")).is_empty());
+
+        let plugins = dir.path().join("plugins");
+        fs::create_dir_all(&plugins).unwrap();
+        let copied = copy_debug_symbols(&release.join("libplugin_chat.dylib"), &plugins).unwrap();
+        assert_eq!(copied, vec![plugins.join("libplugin_chat.dylib.dSYM")]);
+        assert!(plugins.join("libplugin_chat.dylib.dSYM/Contents/Resources/dwarf").exists());
+
+        let copied = copy_debug_symbols(&release.join("plugin_chat.dll"), &plugins).unwrap();
+        assert_eq!(copied, vec![plugins.join("plugin_chat.pdb")]);
+    }
+
+    #[test]
+    fn test_find_built_library_ignores_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let ext = std::env::consts::DLL_EXTENSION;
+        fs::write(dir.path().join(format!("libPlugin_Chat.{ext}")), "chat").unwrap();
+        fs::write(dir.path().join(format!("libplugin_chat_extras.{ext}")), "extras").unwrap();
+
+        let found = find_built_library_in_workspace(dir.path(), "plugin_chat", HOST_TARGET).unwrap();
+        assert_eq!(found, dir.path().join(format!("libPlugin_Chat.{ext}")));
+        let found = find_built_library_in_workspace(dir.path(), "Plugin-Chat", HOST_TARGET).unwrap();
+        assert_eq!(found, dir.path().join(format!("libPlugin_Chat.{ext}")));
+
+        assert_eq!(library_match_rank("PLUGIN_CHAT.DLL", "plugin_chat"), 2);
+        assert_eq!(library_match_rank("plugin_chat_extras.dll", "plugin_chat"), 1);
+        assert_eq!(library_match_rank("plugin_greeter.dll", "plugin_chat"), 0);
+    }
+
+    #[test]
+    fn test_copy_removes_case_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("libplugin_chat.so");
+        fs::write(&lib, "new").unwrap();
+        let plugins = dir.path().join("horizon/plugins");
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(plugins.join("libPlugin_Chat.so"), "old").unwrap();
+
+        let copied = copy_to_horizon_plugins(&lib, &dir.path().join("horizon"), None, None).unwrap();
+        assert_eq!(copied, plugins.join("libplugin_chat.so"));
+        let names: Vec<_> = fs::read_dir(&plugins).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["libplugin_chat.so"]);
+        assert_eq!(fs::read_to_string(copied).unwrap(), "new");
+    }
+
+    #[test]
+    fn test_build_manifest_written_next_to_library() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"plugin_chat\"\nversion = \"0.2.0\"\n").unwrap();
+        let library = dir.path().join("libplugin_chat.so");
+        fs::write(&library, "library").unwrap();
+
+        let manifest = BuildManifest::for_library(dir.path(), &library, BuildProfile::Release, HOST_TARGET).unwrap();
+        let path = manifest.write_next_to(&library).unwrap();
+        assert_eq!(path, dir.path().join("libplugin_chat.so.manifest.json"));
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["name"], "plugin_chat");
+        assert_eq!(json["version"], "0.2.0");
+        assert_eq!(json["library"], "libplugin_chat.so");
+        assert_eq!(json["sha256"], sha256_file(&library).unwrap());
+        assert_eq!(json["profile"], "release");
+        assert_eq!(json["target"], HOST_TARGET);
+    }
+
+    #[test]
+    fn test_find_built_library_for_cross_target() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("plugin_chat.dll"), "windows").unwrap();
+        fs::write(dir.path().join("libplugin_chat.so"), "linux").unwrap();
+
+        let found = find_built_library_in_workspace(dir.path(), "plugin_chat", "x86_64-pc-windows-gnu").unwrap();
+        assert_eq!(found, dir.path().join("plugin_chat.dll"));
+        let found = find_built_library_in_workspace(dir.path(), "plugin_chat", "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(found, dir.path().join("libplugin_chat.so"));
+        assert!(find_built_library_in_workspace(dir.path(), "plugin_chat", "aarch64-apple-darwin").is_err());
+    }
+
+    #[test]
+    fn test_workspace_collision_outside_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("plugin_chat")).unwrap();
+
+        // Only workspace crates/ directories are checked
+        assert!(check_workspace_collision(dir.path(), "chat").is_ok());
+    }
+
+    #[test]
+    fn test_doctor_fixes_manifest() {
+        let mut doc = "[package]\nname = \"chat-bot\"\nversion = \"0.1.0\"\n"
+            .parse::<Document>()
+            .unwrap();
+
+        let issues = diagnose_plugin_manifest(&doc, None);
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].fix, Some(DoctorFix::PackageName("plugin_chat_bot".to_string())));
+        assert_eq!(issues[1].fix, Some(DoctorFix::CrateType));
+        assert!(issues[2].fix.is_none());
+
+        let issues = diagnose_plugin_manifest(&doc, Some("0.3.0"));
+        for issue in &issues {
+            apply_doctor_fix(&mut doc, issue.fix.as_ref().unwrap()).unwrap();
+        }
+
+        assert!(diagnose_plugin_manifest(&doc, Some("0.3.0")).is_empty());
+        assert_eq!(doc["package"]["name"].as_str(), Some("plugin_chat_bot"));
+        assert_eq!(doc["dependencies"]["horizon_event_system"].as_str(), Some("0.3.0"));
+
+        // A crate identified by its Horizon metadata keeps its own name
+        let mut doc = "[package]\nname = \"inventory\"\n\n[package.metadata.horizon]\nplugin_id = \"inventory\"\n"
+            .parse::<Document>()
+            .unwrap();
+        let issues = diagnose_plugin_manifest(&doc, Some("0.3.0"));
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| !matches!(issue.fix, Some(DoctorFix::PackageName(_)))));
+        for issue in &issues {
+            apply_doctor_fix(&mut doc, issue.fix.as_ref().unwrap()).unwrap();
+        }
+        assert!(diagnose_plugin_manifest(&doc, Some("0.3.0")).is_empty());
+        assert_eq!(doc["package"]["name"].as_str(), Some("inventory"));
+    }
+
+    #[test]
+    fn test_horizon_event_system_version_from_workspace() {
+        let horizon = tempfile::tempdir().unwrap();
+        let crate_dir = horizon.path().join("crates/horizon_event_system");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(crate_dir.join("Cargo.toml"), "[package]\nname = \"horizon_event_system\"\nversion.workspace = true\n").unwrap();
+        fs::write(horizon.path().join("Cargo.toml"), "[workspace]\nmembers = []\n\n[workspace.package]\nversion = \"0.4.1\"\n").unwrap();
+
+        assert_eq!(horizon_event_system_version(horizon.path()).as_deref(), Some("0.4.1"));
+        assert_eq!(horizon_event_system_version(&horizon.path().join("missing")), None);
+    }
+
+    #[test]
+    fn test_check_plugin_argument_matches() {
+        assert!(check_plugin_argument_matches("foo", "plugin_foo").is_ok());
+        assert!(check_plugin_argument_matches("plugin_foo", "plugin_foo").is_ok());
+        let err = check_plugin_argument_matches("bar", "plugin_foo").unwrap_err();
+        assert!(err.to_string().contains("plugin_foo"));
+    }
+
+    #[test]
+    fn test_resolve_template_branch() {
+        let branches = vec!["main".to_string(), "next".to_string()];
+
+        assert_eq!(resolve_template_branch(&branches, Some("main"), None).unwrap().as_deref(), Some("main"));
+        assert_eq!(resolve_template_branch(&branches, None, None).unwrap(), None);
+        assert_eq!(resolve_template_branch(&branches, Some("main"), Some("next")).unwrap().as_deref(), Some("next"));
+
+        let err = resolve_template_branch(&branches, Some("main"), Some("master")).unwrap_err().to_string();
+        assert!(err.contains("'master'"));
+        assert!(err.contains("main, next (default: main)"));
+    }
+
+    #[test]
+    fn test_find_workspace_roots_nested() {
+        let outer = tempfile::tempdir().unwrap();
+        let inner = outer.path().join("engine");
+        let plugin_dir = inner.join("crates/plugin_chat");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        fs::write(outer.path().join("Cargo.toml"), "[workspace]\nmembers = [\"engine\"]\n").unwrap();
+        fs::write(inner.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        fs::write(plugin_dir.join("Cargo.toml"), "[package]\nname = \"plugin_chat\"\n").unwrap();
+
+        let roots = find_workspace_roots(&plugin_dir).unwrap();
+        assert_eq!(roots, vec![inner.clone(), outer.path().to_path_buf()]);
+
+        let standalone = tempfile::tempdir().unwrap();
+        assert!(find_workspace_roots(standalone.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_cargo_toml_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_path = dir.path().join("Cargo.toml");
+        fs::write(&cargo_path, "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\n").unwrap();
+
+        let overrides = vec![
+            parse_cargo_override("package.authors=[\"Ada\", \"Grace\"]").unwrap(),
+            parse_cargo_override("package.repository = https://example.com/chat").unwrap(),
+            parse_cargo_override("package.metadata.horizon.plugin_id=\"chat\"").unwrap(),
+        ];
+        update_cargo_toml(dir.path(), "chat", &[], &overrides).unwrap();
+
+        let doc = fs::read_to_string(&cargo_path).unwrap().parse::<Document>().unwrap();
+        assert_eq!(doc["package"]["name"].as_str(), Some("plugin_chat"));
+        assert_eq!(doc["package"]["authors"].as_array().unwrap().len(), 2);
+        assert_eq!(doc["package"]["repository"].as_str(), Some("https://example.com/chat"));
+        assert_eq!(doc["package"]["metadata"]["horizon"]["plugin_id"].as_str(), Some("chat"));
+
+        assert!(parse_cargo_override("package.name").is_err());
+        assert!(parse_cargo_override("package..name=x").is_err());
+        assert!(update_cargo_toml(dir.path(), "chat", &[], &[("package.name.inner".to_string(), "1".to_string())]).is_err());
+    }
+
+    #[test]
+    fn test_template_url_validation() {
+        assert!(looks_like_git_remote(SAMPLE_REPO_URL));
+        assert!(looks_like_git_remote("git@github.com:acme/plugin-template.git"));
+        assert!(looks_like_git_remote("ssh://git@git.acme.dev:2222/plugins/template.git"));
+        assert!(!looks_like_git_remote("acme/plugin-template"));
+        assert!(!looks_like_git_remote("https://"));
+        assert!(!looks_like_git_remote("not a url"));
+
+        assert_eq!(template_repo_name("git@github.com:acme/plugin-template.git"), "plugin-template");
+        assert_eq!(template_mirror_name(SAMPLE_REPO_URL), "Horizon-Plugin-Sample.git");
+        let custom = template_mirror_name("https://git.acme.dev/plugins/Horizon-Plugin-Sample.git");
+        assert!(custom.starts_with("Horizon-Plugin-Sample-") && custom.ends_with(".git"));
+        assert_ne!(custom, template_mirror_name("https://git.other.dev/plugins/Horizon-Plugin-Sample.git"));
+    }
+
+    #[test]
+    fn test_resolve_template() {
+        let mut config = Config::default();
+        config.templates.insert(
+            "team".to_string(),
+            crate::config::TemplateConfig { url: "git@git.acme.dev:team/template.git".to_string(), description: None },
+        );
+
+        assert_eq!(resolve_template("sample", Config::default()).unwrap(), Template::Git(SAMPLE_REPO_URL.to_string()));
+        assert_eq!(resolve_template("builtin", Config::default()).unwrap(), Template::Embedded);
+        assert_eq!(
+            resolve_template("team", config).unwrap(),
+            Template::Git("git@git.acme.dev:team/template.git".to_string())
+        );
+        assert_eq!(
+            resolve_template("https://git.acme.dev/other.git", Config::default()).unwrap(),
+            Template::Git("https://git.acme.dev/other.git".to_string())
+        );
+        assert!(resolve_template("team", Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_find_horizon_plugins() {
+        let root = horizon_workspace(r#""crates/*""#);
+        let crates = root.path().join("crates");
+        for (dir, manifest) in [
+            ("plugin_chat", "[package]\nname = \"plugin_chat\"\nversion = \"0.2.0\"\n"),
+            ("plugin_system", "[package]\nname = \"plugin_system\"\n"),
+            ("inventory", "[package]\nname = \"inventory\"\nversion.workspace = true\n\n[package.metadata.horizon]\n"),
+            ("shared", "[package]\nname = \"shared\"\n"),
+        ] {
+            fs::create_dir_all(crates.join(dir)).unwrap();
+            fs::write(crates.join(dir).join("Cargo.toml"), manifest).unwrap();
+        }
+        fs::create_dir_all(crates.join("plugin_empty")).unwrap();
+
+        let plugins = find_horizon_plugins(&crates).unwrap();
+        assert_eq!(plugins, vec![crates.join("inventory"), crates.join("plugin_chat")]);
+        assert_eq!(read_package_version(&crates.join("plugin_chat/Cargo.toml")).unwrap(), "0.2.0");
+        assert_eq!(read_package_version(&crates.join("inventory/Cargo.toml")).unwrap(), "workspace");
+    }
+
+    #[test]
+    fn test_embedded_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("chat");
+        write_embedded_template(&plugin_dir).unwrap();
+        update_cargo_toml(&plugin_dir, "chat", &[], &[]).unwrap();
+        update_plugin_code(&plugin_dir, "chat", &default_struct_name("chat"), false).unwrap();
+
+        assert_eq!(read_package_name(&plugin_dir.join("Cargo.toml")).unwrap(), "plugin_chat");
+        let doc = fs::read_to_string(plugin_dir.join("Cargo.toml")).unwrap().parse::<Document>().unwrap();
+        assert!(diagnose_plugin_manifest(&doc, None).is_empty());
+        assert!(fs::read_to_string(plugin_dir.join("src/lib.rs")).unwrap().contains("ChatPlugin"));
+        assert!(plugin_dir.join(".gitignore").exists());
+
+        assert!(write_embedded_template(&plugin_dir).is_err());
+    }
+
+    #[test]
+    fn test_update_cargo_toml_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_path = dir.path().join("Cargo.toml");
+        fs::write(&cargo_path, "[package]\nname = \"greeter\"\n\n[dependencies]\nserde = \"1.0\"\n").unwrap();
+
+        let dependencies = vec![("rand".to_string(), "0.8.5".to_string()), ("reqwest".to_string(), "0.11".to_string())];
+        update_cargo_toml(dir.path(), "chat", &dependencies, &[]).unwrap();
+
+        let doc = fs::read_to_string(&cargo_path).unwrap().parse::<Document>().unwrap();
+        assert_eq!(doc["dependencies"]["serde"].as_str(), Some("1.0"));
+        assert_eq!(doc["dependencies"]["rand"].as_str(), Some("0.8.5"));
+        assert_eq!(doc["dependencies"]["reqwest"].as_str(), Some("0.11"));
+
+        assert_eq!(parse_dependency_arg("rand").unwrap(), ("rand".to_string(), None));
+        assert_eq!(parse_dependency_arg("reqwest=0.11").unwrap(), ("reqwest".to_string(), Some("0.11".to_string())));
+
+        assert!(parse_dependency_arg("serde_json = 1").is_ok());
+        assert!(parse_dependency_arg("reqwest=").is_err());
+        assert!(parse_dependency_arg("1password").is_err());
+        assert!(parse_dependency_arg("my crate").is_err());
+    }
+
+    #[test]
+    fn test_crate_version_from_response() {
+        let body = serde_json::json!({"crate": {"max_version": "0.9.0-alpha.1", "max_stable_version": "0.8.5"}});
+        assert_eq!(crate_version_from_response(&body).as_deref(), Some("0.8.5"));
+        let body = serde_json::json!({"crate": {"max_version": "0.1.0-beta", "max_stable_version": null}});
+        assert_eq!(crate_version_from_response(&body).as_deref(), Some("0.1.0-beta"));
+        assert_eq!(crate_version_from_response(&serde_json::json!({"errors": []})), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_dependency_versions_offline() {
+        let pinned = vec![("reqwest".to_string(), Some("0.11".to_string()))];
+        assert_eq!(
+            resolve_dependency_versions(&pinned, true).await.unwrap(),
+            [("reqwest".to_string(), "0.11".to_string())]
+        );
+        let bare = vec![("rand".to_string(), None)];
+        let err = resolve_dependency_versions(&bare, true).await.unwrap_err();
+        assert!(err.to_string().contains("--dependency rand=1"));
+    }
+
+    #[test]
+    fn test_verify_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("libplugin_chat.so");
+        let good = dir.path().join("good.so");
+        let truncated = dir.path().join("truncated.so");
+        let corrupted = dir.path().join("corrupted.so");
+        fs::write(&source, b"plugin bytes").unwrap();
+        fs::write(&good, b"plugin bytes").unwrap();
+        fs::write(&truncated, b"plugin").unwrap();
+        fs::write(&corrupted, b"plugin bytez").unwrap();
+
+        assert!(verify_copy(&source, &good).is_ok());
+        assert!(verify_copy(&source, &truncated).unwrap_err().to_string().contains("incomplete"));
+        assert!(verify_copy(&source, &corrupted).unwrap_err().to_string().contains("SHA-256"));
+    }
+
+    #[test]
+    fn test_out_of_space_detection() {
+        assert!(is_out_of_space(&std::io::Error::from(std::io::ErrorKind::StorageFull)));
+        assert!(!is_out_of_space(&std::io::Error::from(std::io::ErrorKind::PermissionDenied)));
+        #[cfg(target_os = "linux")]
+        assert!(is_out_of_space(&std::io::Error::from_raw_os_error(28))); // ENOSPC
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = filesystem_root(&dir.path().join("plugins/libplugin_chat.so"));
+        assert!(fs::canonicalize(dir.path()).unwrap().starts_with(&root));
+    }
+
+    #[test]
+    fn test_copy_to_horizon_plugins_replaces_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("libplugin_chat.so");
+        let horizon = dir.path().join("Horizon");
+        fs::create_dir_all(horizon.join("plugins")).unwrap();
+        fs::write(horizon.join("plugins/libplugin_chat.so"), b"old build").unwrap();
+        fs::write(&lib, b"new build").unwrap();
+
+        let copied = copy_to_horizon_plugins(&lib, &horizon, None, None).unwrap();
+        assert_eq!(copied, horizon.join("plugins/libplugin_chat.so"));
+        assert_eq!(fs::read(&copied).unwrap(), b"new build");
+        let leftovers: Vec<_> = fs::read_dir(horizon.join("plugins")).unwrap().collect();
+        assert_eq!(leftovers.len(), 1, "staging file should have been renamed away");
+    }
+
+    #[test]
+    fn test_add_test_harness_dependencies() {
+        let mut doc = "[package]\nname = \"plugin_chat\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n\n[dependencies]\nasync-trait = \"0.1\"\n"
+            .parse::<Document>()
+            .unwrap();
+        add_test_harness_dependencies(&mut doc).unwrap();
+        add_test_harness_dependencies(&mut doc).unwrap();
+
+        let crate_types: Vec<_> = doc["lib"]["crate-type"].as_array().unwrap()
+            .iter()
+            .filter_map(|t| t.as_str())
+            .collect();
+        assert_eq!(crate_types, vec!["cdylib", "rlib"]);
+        assert!(doc["dev-dependencies"]["tokio"].is_inline_table());
+        assert!(doc["dev-dependencies"].get("async-trait").is_none());
+    }
+
+    #[test]
+    fn test_lifecycle_test_template_names() {
+        let template = create_lifecycle_test_template("plugin_chat-bot", "ChatBotPlugin");
+        assert!(template.contains("use plugin_chat_bot::ChatBotPlugin;"));
+        assert!(template.contains("ChatBotPlugin::new()"));
+    }
+
+    fn new_args(args: &[&str]) -> NewArgs {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            args: NewArgs,
+        }
+        <Cli as clap::Parser>::try_parse_from(std::iter::once("new").chain(args.iter().copied()))
+            .unwrap()
+            .args
+    }
+
+    #[tokio::test]
+    async fn test_new_plugin_force_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        // Conventions fail by default and are only warnings with --force-name
+        let err = create_new_plugin(new_args(&["_chat", "--offline", "--vcs", "none", "--path", path])).await.unwrap_err();
+        assert!(err.to_string().contains("underscore"));
+        assert!(!dir.path().join("_chat").exists());
+
+        create_new_plugin(new_args(&["_chat", "--offline", "--vcs", "none", "--path", path, "--force-name"])).await.unwrap();
+        let manifest = fs::read_to_string(dir.path().join("_chat/Cargo.toml")).unwrap();
+        assert_eq!(manifest.parse::<Document>().unwrap()["package"]["name"].as_str(), Some("plugin__chat"));
+        assert!(fs::read_to_string(dir.path().join("_chat/src/lib.rs")).unwrap().contains("ChatPlugin"));
+
+        // Names cargo or the generated struct can't accept are rejected even with --force-name
+        for name in ["-chat", "9lives", "my.plugin"] {
+            let args = new_args(&["--offline", "--vcs", "none", "--path", path, "--force-name", "--", name]);
+            assert!(create_new_plugin(args).await.is_err(), "{} was accepted", name);
+            assert!(!dir.path().join(name).exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_mirror_online_then_cached() {
+        use git2::{Repository, Signature};
+
+        let upstream_dir = tempfile::tempdir().unwrap();
+        {
+            let upstream = Repository::init(upstream_dir.path()).unwrap();
+            fs::write(upstream_dir.path().join("Cargo.toml"), "[package]\nname = \"greeter\"\n").unwrap();
+            let mut index = upstream.index().unwrap();
+            index.add_path(Path::new("Cargo.toml")).unwrap();
+            let tree = upstream.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = Signature::now("test", "test@example.com").unwrap();
+            let commit = upstream.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+            upstream.branch("next", &upstream.find_commit(commit).unwrap(), false).unwrap();
+        }
+
+        let cache = tempfile::tempdir().unwrap();
+        let mut source = TemplateSource {
+            url: upstream_dir.path().to_str().unwrap().to_string(),
+            mirror: cache.path().join("sample.git"),
+            offline: true,
+            refresh: false,
+        };
+        let work = tempfile::tempdir().unwrap();
+
+        // Nothing cached yet
+        assert!(clone_sample_repo(&work.path().join("a"), None, &source, Arc::default()).await.is_err());
+
+        source.offline = false;
+        clone_sample_repo(&work.path().join("b"), None, &source, Arc::default()).await.unwrap();
+        assert!(work.path().join("b/Cargo.toml").exists());
+        assert!(template_mirror_is_fresh(&source.mirror));
+
+        // Served from the mirror even though the upstream is gone
+        source.offline = true;
+        fs::remove_dir_all(upstream_dir.path()).unwrap();
+        clone_sample_repo(&work.path().join("c"), Some("next"), &source, Arc::default()).await.unwrap();
+        assert!(work.path().join("c/Cargo.toml").exists());
+
+        // Ctrl-C during `plugin new` stops the clone
+        let err = clone_sample_repo(&work.path().join("d"), None, &source, Arc::new(AtomicBool::new(true))).await.unwrap_err();
+        assert_eq!(err.to_string(), "Interrupted");
+    }
+
+    #[test]
+    fn test_read_package_name_malformed_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+
+        fs::write(&manifest, "[package]\nname = \"plugin_chat\"\n").unwrap();
+        assert_eq!(read_package_name(&manifest).unwrap(), "plugin_chat");
+
+        fs::write(&manifest, "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        let err = read_package_name(&manifest).unwrap_err().to_string();
+        assert!(err.contains("workspace manifest"), "{}", err);
+
+        fs::write(&manifest, "[package]\nversion = \"0.1.0\"\n").unwrap();
+        let err = read_package_name(&manifest).unwrap_err().to_string();
+        assert!(err.contains("missing [package] name"), "{}", err);
+
+        fs::write(&manifest, "[dependencies]\nserde = \"1\"\n").unwrap();
+        let err = read_package_name(&manifest).unwrap_err().to_string();
+        assert!(err.contains("no [package] table"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("HUP").unwrap(), "HUP");
+        assert_eq!(parse_signal("sigusr1").unwrap(), "USR1");
+        assert_eq!(parse_signal("1").unwrap(), "1");
+        assert!(parse_signal("SIG").is_err());
+        assert!(parse_signal("HUP; rm").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use colored::*;
+use console::style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Input;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{config_path, remove_settings, write_settings, Config};
+use crate::utils::{command_exists, github_token};
+
+/// Organization the repo commands use when none is configured
+const DEFAULT_GITHUB_ORG: &str = "Far-Beyond-Dev";
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Horizon checkout that `plugin build` copies plugins into
+    #[arg(long, value_name = "PATH")]
+    horizon_path: Option<PathBuf>,
+    /// GitHub organization for the repo commands
+    #[arg(long, value_name = "ORG")]
+    github_org: Option<String>,
+    /// Command that prints a GitHub token, e.g. "gh auth token"
+    #[arg(long, value_name = "COMMAND")]
+    token_command: Option<String>,
+    /// Create this directory to clone repositories into
+    #[arg(long, value_name = "DIR")]
+    workspace: Option<PathBuf>,
+    /// Don't prompt; use the flags given and keep or default everything else
+    #[arg(long, short = 'y')]
+    yes: bool,
+}
+
+pub fn run(args: InitArgs) -> Result<()> {
+    let path = config_path()?;
+    let existing = Config::load()?;
+
+    outln!("🔧 Setting up fbcli");
+    outln!("📄 Config file: {}", style(path.display()).yellow());
+    if path.exists() {
+        outln!("ℹ Existing settings are kept unless you change them");
+    }
+    outln!();
+
+    let interactive = !args.yes;
+    let mut settings = Vec::new();
+    // Settings answered with nothing are removed so an existing value can be cleared
+    let mut cleared = Vec::new();
+
+    let horizon_default = existing.horizon_path
+        .or_else(|| Some(PathBuf::from("../Horizon")).filter(|path| path.is_dir()))
+        .map(|path| absolute(&path).display().to_string());
+    let horizon_path = ask(
+        interactive,
+        args.horizon_path.map(|path| path.display().to_string()),
+        "Path to your Horizon checkout (empty for none):",
+        horizon_default,
+    )?;
+    if let Some(horizon_path) = horizon_path {
+        let horizon_path = absolute(Path::new(&horizon_path));
+        if !horizon_path.join("Cargo.toml").is_file() {
+            outln!("⚠️  {} doesn't look like a Horizon checkout yet; saving it anyway", horizon_path.display());
+        }
+        settings.push(("horizon_path", horizon_path.display().to_string()));
+    } else {
+        cleared.push("horizon_path");
+    }
+
+    let github_org = ask(
+        interactive,
+        args.github_org,
+        "GitHub organization:",
+        Some(existing.github_org.unwrap_or_else(|| DEFAULT_GITHUB_ORG.to_string())),
+    )?;
+    if let Some(github_org) = github_org {
+        settings.push(("github_org", github_org));
+    } else {
+        cleared.push("github_org");
+    }
+
+    // A token from the environment always wins, so only ask when there isn't one
+    let token_default = existing.github_token_command
+        .or_else(|| command_exists("gh").then(|| "gh auth token".to_string()));
+    let token_from_env = args.token_command.is_none()
+        && std::env::var_os("FBCLI_GITHUB_TOKEN").or_else(|| std::env::var_os("GITHUB_TOKEN")).is_some();
+    let token_command = if token_from_env {
+        outln!("✅ Using the GitHub token from FBCLI_GITHUB_TOKEN / GITHUB_TOKEN");
+        None
+    } else {
+        ask(
+            interactive,
+            args.token_command,
+            "Command that prints a GitHub token, for private repositories (empty for none):",
+            token_default,
+        )?
+    };
+    match token_command {
+        Some(token_command) => settings.push(("github_token_command", token_command)),
+        None if !token_from_env => cleared.push("github_token_command"),
+        None => {}
+    }
+
+    let workspace = ask(
+        interactive,
+        args.workspace.map(|dir| dir.display().to_string()),
+        "Directory to clone Far-Beyond repositories into (empty to skip):",
+        None,
+    )?
+    .map(|dir| absolute(Path::new(&dir)));
+    if let Some(workspace) = &workspace {
+        fs::create_dir_all(workspace)?;
+    }
+
+    write_settings(&path, &settings)?;
+    remove_settings(&path, &cleared)?;
+
+    outln!();
+    outln!("{}", "✅ fbcli is set up!".green().bold());
+    outln!("📄 Wrote {}", style(path.display()).yellow());
+    for (key, value) in &settings {
+        outln!("  {} = {}", key, style(value).cyan());
+    }
+    if settings.iter().any(|(key, _)| *key == "github_token_command") && github_token().is_none() {
+        outln!("⚠️  The token command didn't print a token; private repositories won't be listed until it does");
+    }
+    if let Some(workspace) = workspace {
+        outln!("📁 Workspace: {}", style(workspace.display()).yellow());
+        outln!();
+        outln!("{}", "Next steps:".bold());
+        outln!("  cd {}", workspace.display());
+        outln!("  fbcli repo clone-all");
+    }
+    Ok(())
+}
+
+/// Take the flag if given; otherwise prompt (or use the default when not
+/// interactive). The default is pre-filled for editing, so erasing it and
+/// answering with nothing means "none".
+fn ask(interactive: bool, flag: Option<String>, question: &str, default: Option<String>) -> Result<Option<String>> {
+    let answer = match flag {
+        Some(value) => value,
+        None if interactive => {
+            // dialoguer prompts on stderr, so the questions stay visible with --quiet
+            let theme = ColorfulTheme::default();
+            let mut input = Input::<String>::with_theme(&theme)
+                .with_prompt(question)
+                .allow_empty(true);
+            if let Some(default) = default {
+                input = input.with_initial_text(default);
+            }
+            input
+                .interact_text()
+                .context("Cannot prompt without a terminal; pass --yes with the settings as flags")?
+        }
+        None => default.unwrap_or_default(),
+    };
+    let answer = answer.trim();
+    Ok((!answer.is_empty()).then(|| answer.to_string()))
+}
+
+/// Make a path absolute so the config works from any directory
+pub fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| std::env::current_dir().map(|dir| dir.join(path)))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
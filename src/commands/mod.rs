@@ -1,2 +1,5 @@
+pub mod doctor;
 pub mod horizon;
-pub mod repo;
\ No newline at end of file
+pub mod init;
+pub mod repo;
+pub mod settings;
\ No newline at end of file
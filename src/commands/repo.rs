@@ -1,43 +1,285 @@
-use clap::Subcommand;
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
 use anyhow::{anyhow, Context, Result};
 use colored::*;
 use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::fs;
-use git2::Repository;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use walkdir::WalkDir;
+use git2::{
+    build::RepoBuilder, AutotagOption, BranchType, Cred, CredentialType, FetchOptions, IndexAddOption, Oid, Remote, RemoteCallbacks,
+    Repository, Signature, StatusOptions,
+};
+
+use crate::config::Config;
+use crate::github;
+use crate::summary::{BatchSummary, ItemOutcome, OutcomeStatus};
+use crate::ui;
+use crate::utils::{
+    cache_dir, confirm, current_dir, dir_size, format_bytes, format_relative_time, git_version, github_token, is_git_repository,
+    is_rust_project, open_in_browser, parse_github_remote, render_table, GitHubRemote,
+};
 
 const GITHUB_ORG: &str = "Far-Beyond-Dev";
 const GITHUB_API_BASE: &str = "https://api.github.com";
+const GITHUB_HOST: &str = "github.com";
+/// First git release whose `clone --filter` is reliable for partial clones
+const MIN_PARTIAL_CLONE_GIT: (u32, u32, u32) = (2, 22, 0);
 
 #[derive(Subcommand)]
 pub enum RepoCommand {
     /// List all repositories in the Far-Beyond-Dev organization
-    List {
-        /// Show only public repositories
-        #[arg(long)]
-        public_only: bool,
-    },
+    List(ListArgs),
     /// Clone a repository from Far-Beyond-Dev
-    Clone {
-        /// Repository name
-        repo: String,
-        /// Target directory (defaults to repo name)
-        #[arg(short, long)]
-        path: Option<PathBuf>,
-        /// Use SSH instead of HTTPS
-        #[arg(long)]
-        ssh: bool,
-    },
+    Clone(CloneArgs),
+    /// Clone every Far-Beyond-Dev repository into the current directory
+    CloneAll(CloneAllArgs),
     /// Update all Far-Beyond repositories in current directory
-    Update {
-        /// Perform a dry run (show what would be updated)
-        #[arg(long)]
-        dry_run: bool,
-    },
+    Update(UpdateArgs),
     /// Check status of all Far-Beyond repositories
-    Status,
+    Status(StatusArgs),
+    /// Show who last committed to each Far-Beyond repository, and when
+    BlameOrg(BlameOrgArgs),
+    /// Rename a local checkout to match its (possibly renamed) GitHub repository
+    #[command(visible_alias = "mv")]
+    Rename(RenameArgs),
+    /// Open a repository's GitHub page in the browser
+    Open(OpenArgs),
+    /// Delete local branches that are merged into the default branch or whose upstream is gone
+    PruneBranches(PruneBranchesArgs),
+    /// Change the origin remote URL of the current repository
+    SetRemote {
+        /// New remote URL (defaults to converting the existing URL)
+        url: Option<String>,
+        /// Convert the existing URL to SSH form (otherwise converts to HTTPS)
+        #[arg(long, conflicts_with = "url")]
+        ssh: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Show only public repositories
+    #[arg(long)]
+    public_only: bool,
+    /// Show a compact table sorted by most recently updated
+    #[arg(long)]
+    recent: bool,
+    /// Print one JSON object per repository per line (NDJSON), without the banner
+    #[arg(long, conflicts_with = "recent")]
+    json_lines: bool,
+    /// Print the repositories as a JSON array, without the banner
+    #[arg(long, conflicts_with_all = ["recent", "json_lines"])]
+    json: bool,
+    /// Mark which repositories are already cloned in the current directory
+    #[arg(long, conflicts_with_all = ["json_lines", "json"])]
+    compare_local: bool,
+    /// With --compare-local, list only repositories that aren't cloned yet
+    #[arg(long, requires = "compare_local")]
+    missing_only: bool,
+    #[command(flatten)]
+    host: GitHubHostArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CloneArgs {
+    /// Repository name
+    repo: String,
+    /// Target directory (defaults to repo name)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+    /// Use SSH instead of HTTPS
+    #[arg(long)]
+    ssh: bool,
+    /// Skip checking that the repository exists on GitHub before cloning
+    #[arg(long)]
+    no_verify: bool,
+    /// Blobless partial clone (--filter=blob:none); file contents are fetched on demand
+    #[arg(long)]
+    partial: bool,
+    /// Check out this branch instead of the repository's default branch
+    #[arg(long, short = 'b', value_name = "NAME")]
+    branch: Option<String>,
+    #[command(flatten)]
+    host: GitHubHostArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CloneAllArgs {
+    /// Skip private repositories
+    #[arg(long)]
+    public_only: bool,
+    /// Use SSH instead of HTTPS
+    #[arg(long)]
+    ssh: bool,
+    /// Number of repositories to clone at the same time
+    #[arg(long, short = 'j', value_name = "N", default_value_t = DEFAULT_CLONE_ALL_JOBS, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: u32,
+    /// Write a JSON report of per-repository outcomes to this path
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+    #[command(flatten)]
+    host: GitHubHostArgs,
+}
+
+/// Overrides for talking to a GitHub Enterprise instance instead of github.com
+#[derive(Args, Debug, Default)]
+pub struct GitHubHostArgs {
+    /// GitHub API base URL (env FBCLI_GITHUB_API_BASE, config github_api_base)
+    #[arg(long, value_name = "URL")]
+    api_base: Option<String>,
+    /// Host to clone from (env FBCLI_GITHUB_GIT_HOST, config github_git_host)
+    #[arg(long, value_name = "HOST")]
+    git_host: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    /// Perform a dry run (show what would be updated)
+    #[arg(long)]
+    dry_run: bool,
+    /// Also fetch tags and report newly fetched ones
+    #[arg(long)]
+    tags: bool,
+    /// Commit uncommitted changes as a WIP commit before updating
+    #[arg(long)]
+    wip_commit: bool,
+    /// Check out this tag, branch or commit in every repository (detached HEAD)
+    #[arg(long, value_name = "REF", conflicts_with = "wip_commit")]
+    onto: Option<String>,
+    /// Rebase local commits onto the fetched branch instead of failing when a fast-forward isn't possible
+    #[arg(long, conflicts_with = "onto")]
+    rebase: bool,
+    /// Stash uncommitted changes before updating and restore them afterwards (dirty repositories are skipped otherwise)
+    #[arg(long, conflicts_with_all = ["wip_commit", "onto"])]
+    autostash: bool,
+    /// What to do when the autostash conflicts with the update
+    #[arg(long, alias = "autostash-pop-strategy", value_enum, value_name = "STRATEGY", default_value_t = AutostashConflict::LeaveMarkers, requires = "autostash")]
+    autostash_conflict: AutostashConflict,
+    /// Retry a fetch this many times on network errors, with backoff
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    max_retries: u32,
+    /// Number of repositories to update at the same time
+    #[arg(long, short = 'j', value_name = "N", default_value_t = DEFAULT_UPDATE_JOBS, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: u32,
+    /// Only print repositories that changed or failed, with a count of those already up to date
+    #[arg(long, alias = "report-only-changed")]
+    changed_only: bool,
+    /// Write a JSON report of per-repository outcomes to this path
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+    #[command(flatten)]
+    scan: ScanArgs,
+}
+
+/// Handling of an `--autostash` that doesn't apply cleanly after the update
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AutostashConflict {
+    /// Keep the stash and reset the working tree to the updated branch
+    Keep,
+    /// Roll the branch back to where it was and restore the changes there
+    Abort,
+    /// Leave conflict markers in the working tree (the stash is kept too)
+    LeaveMarkers,
+}
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// Check each repository's checked-out branch against the default branch on GitHub
+    #[arg(long)]
+    verify_remote: bool,
+    /// With --verify-remote, ask GitHub again even for default branches cached recently
+    #[arg(long, requires = "verify_remote")]
+    refresh: bool,
+    /// Also report git checkouts that cannot be opened or whose HEAD is unreadable
+    #[arg(long)]
+    health: bool,
+    /// Group repositories by the owner in their origin URL, with per-owner counts
+    #[arg(long)]
+    group_by_owner: bool,
+    /// Fetch from origin first so ahead/behind counts reflect the remote as it is now
+    #[arg(long)]
+    fetch: bool,
+    /// Print each repository's status as a JSON array instead of text
+    #[arg(long, conflicts_with_all = ["group_by_owner", "verify_remote"])]
+    json: bool,
+    /// Write a JSON report of per-repository outcomes to this path
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+    #[command(flatten)]
+    scan: ScanArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct OpenArgs {
+    /// Repository name in the org (defaults to the repository in the current directory)
+    repo: Option<String>,
+    /// Open the pull requests page
+    #[arg(long, conflicts_with = "issues")]
+    pulls: bool,
+    /// Open the issues page
+    #[arg(long)]
+    issues: bool,
+    /// Print the URL instead of opening it
+    #[arg(long)]
+    print: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BlameOrgArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct PruneBranchesArgs {
+    /// Prune every Far-Beyond repository in the current directory instead of just the current one
+    #[arg(long)]
+    all: bool,
+    /// Also delete branches whose upstream is gone even if they aren't merged
+    #[arg(long)]
+    force: bool,
+    /// Only list the branches that would be deleted
+    #[arg(long)]
+    dry_run: bool,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    #[command(flatten)]
+    scan: ScanArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct RenameArgs {
+    /// Repository checkout to rename (defaults to current directory)
+    path: Option<PathBuf>,
+    /// Also point origin at the new owner/name if the repository moved
+    #[arg(long)]
+    update_remote: bool,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Filters applied when scanning a directory for Far-Beyond repositories
+#[derive(Args, Debug, Clone, Default)]
+pub struct ScanArgs {
+    /// Only include repositories that contain a Cargo.toml
+    #[arg(long)]
+    only_rust: bool,
+    /// Look for repositories in subdirectories at any depth, not just the current directory
+    #[arg(long, short = 'r')]
+    recursive: bool,
+    /// Also include repositories whose origin isn't owned by the organization, e.g. forks
+    #[arg(long)]
+    include_forks: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -53,76 +295,452 @@ struct GitHubRepo {
     updated_at: String,
 }
 
+/// The GitHub instance fbcli talks to: public GitHub unless overridden
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GitHubHost {
+    api_base: String,
+    git_host: String,
+    /// Organization the repo commands list, clone and scan for
+    org: String,
+}
+
+/// The GitHub API base URL the repo commands use without `--api-base`
+pub fn api_base() -> Result<String> {
+    Ok(GitHubHost::resolve(&GitHubHostArgs::default())?.api_base)
+}
+
+impl GitHubHost {
+    /// Resolve from flags, then FBCLI_GITHUB_* env vars, then the config file.
+    /// When only a custom git host is given, the API base is assumed to be the
+    /// Enterprise default of https://<host>/api/v3.
+    fn resolve(args: &GitHubHostArgs) -> Result<Self> {
+        let config = Config::load()?;
+        let org = non_empty_env("FBCLI_GITHUB_ORG")
+            .or(config.github_org)
+            .unwrap_or_else(|| GITHUB_ORG.to_string());
+        let api_base = args.api_base.clone()
+            .or_else(|| non_empty_env("FBCLI_GITHUB_API_BASE"))
+            .or(config.github_api_base);
+        let git_host = args.git_host.clone()
+            .or_else(|| non_empty_env("FBCLI_GITHUB_GIT_HOST"))
+            .or(config.github_git_host);
+        Ok(Self { org, ..Self::from_parts(api_base, git_host) })
+    }
+
+    fn from_parts(api_base: Option<String>, git_host: Option<String>) -> Self {
+        let git_host = git_host.unwrap_or_else(|| GITHUB_HOST.to_string());
+        let api_base = api_base.unwrap_or_else(|| Self::default_api_base(&git_host));
+        Self { api_base: api_base.trim_end_matches('/').to_string(), git_host, org: GITHUB_ORG.to_string() }
+    }
+
+    fn default_api_base(git_host: &str) -> String {
+        if git_host == GITHUB_HOST {
+            GITHUB_API_BASE.to_string()
+        } else {
+            format!("https://{}/api/v3", git_host)
+        }
+    }
+
+    /// API base for a repository whose remote lives on `host`
+    fn api_base_for(&self, host: &str) -> String {
+        if host == self.git_host {
+            self.api_base.clone()
+        } else {
+            Self::default_api_base(host)
+        }
+    }
+
+    fn repo_url(&self, owner: &str, repo: &str, ssh: bool) -> String {
+        let remote = GitHubRemote { host: self.git_host.clone(), owner: owner.to_string(), repo: repo.to_string() };
+        if ssh { remote.ssh_url() } else { remote.https_url() }
+    }
+}
+
+fn non_empty_env(var: &str) -> Option<String> {
+    std::env::var(var).ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// A source of credentials for authenticating git remote operations
+#[derive(Debug, Clone, PartialEq)]
+enum CredentialSource {
+    /// GitHub token from FBCLI_GITHUB_TOKEN or GITHUB_TOKEN (HTTPS)
+    Token,
+    /// Keys loaded into the running ssh-agent
+    SshAgent,
+    /// A private key file on disk
+    SshKey(PathBuf),
+    /// The user's configured git credential helper
+    Helper,
+}
+
+impl CredentialSource {
+    /// All sources worth trying, in order of preference
+    fn candidates() -> Vec<CredentialSource> {
+        let mut sources = vec![CredentialSource::Token, CredentialSource::SshAgent];
+        if let Some(home) = dirs::home_dir() {
+            for key in ["id_ed25519", "id_rsa"] {
+                let path = home.join(".ssh").join(key);
+                if path.exists() {
+                    sources.push(CredentialSource::SshKey(path));
+                }
+            }
+        }
+        sources.push(CredentialSource::Helper);
+        sources
+    }
+
+    fn credential(
+        &self,
+        config: &git2::Config,
+        url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+    ) -> Option<Cred> {
+        let ssh_user = username.unwrap_or("git");
+        match self {
+            CredentialSource::Token if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) => {
+                let token = github_token()?;
+                Cred::userpass_plaintext("x-access-token", &token).ok()
+            }
+            CredentialSource::SshAgent if allowed.contains(CredentialType::SSH_KEY) => {
+                Cred::ssh_key_from_agent(ssh_user).ok()
+            }
+            CredentialSource::SshKey(path) if allowed.contains(CredentialType::SSH_KEY) => {
+                Cred::ssh_key(ssh_user, None, path, None).ok()
+            }
+            CredentialSource::Helper if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) => {
+                Cred::credential_helper(config, url, username).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Credentials provider shared across the repositories of a batch command.
+///
+/// The first source that authenticates successfully is remembered for the rest
+/// of the run, so later fetches go straight to it instead of re-reading tokens
+/// and keys or repeatedly failing against the server.
+#[derive(Clone, Default)]
+pub struct CredentialCache {
+    cached: Arc<Mutex<Option<CredentialSource>>>,
+}
+
+impl CredentialCache {
+    /// Fetch from a remote, authenticating with the cached credential first
+    fn fetch(&self, repo: &Repository, remote: &mut Remote, refspecs: &[&str], download_tags: bool) -> Result<()> {
+        let config = repo.config()?;
+        self.with_credentials(&config, |callbacks| {
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks);
+            if download_tags {
+                fetch_options.download_tags(AutotagOption::All);
+            }
+            remote.fetch(refspecs, Some(&mut fetch_options), None)
+        })
+    }
+
+    /// Clone a repository, authenticating like `fetch` so private repositories
+    /// and SSH URLs work
+    fn clone_repo(&self, url: &str, target_dir: &Path, branch: Option<&str>) -> Result<Repository> {
+        let config = git2::Config::open_default()?;
+        self.with_credentials(&config, |callbacks| {
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks);
+            let mut builder = RepoBuilder::new();
+            builder.fetch_options(fetch_options);
+            if let Some(branch) = branch {
+                builder.branch(branch);
+            }
+            builder.clone(url, target_dir)
+        })
+    }
+
+    /// Run a network operation with a credentials callback that tries the cached
+    /// source first, then remember whichever source got through
+    pub fn with_credentials<T>(
+        &self,
+        config: &git2::Config,
+        operation: impl FnOnce(RemoteCallbacks<'_>) -> std::result::Result<T, git2::Error>,
+    ) -> Result<T> {
+        let cached = self.cached.lock().unwrap().clone();
+        let mut candidates: Vec<CredentialSource> = cached.iter().cloned().collect();
+        for source in CredentialSource::candidates() {
+            if !candidates.contains(&source) {
+                candidates.push(source);
+            }
+        }
+
+        let mut next = 0;
+        let mut used = None;
+        let result = {
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.credentials(|url, username, allowed| {
+                while let Some(source) = candidates.get(next) {
+                    next += 1;
+                    if let Some(cred) = source.credential(config, url, username, allowed) {
+                        used = Some(source.clone());
+                        return Ok(cred);
+                    }
+                }
+                Err(git2::Error::new(
+                    git2::ErrorCode::Auth,
+                    git2::ErrorClass::Callback,
+                    "no usable credentials (set FBCLI_GITHUB_TOKEN or add an SSH key to your agent)",
+                ))
+            });
+            operation(callbacks)
+        };
+
+        let mut cache = self.cached.lock().unwrap();
+        match result {
+            Ok(value) => {
+                if used.is_some() {
+                    *cache = used;
+                }
+                Ok(value)
+            }
+            Err(e) => {
+                // Don't keep offering a credential that just failed
+                if used.is_some() && *cache == used {
+                    *cache = None;
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// `fetch`, retried with exponential backoff while the failure looks transient.
+    /// Returns how many retries were needed.
+    fn fetch_with_retries(
+        &self,
+        repo: &Repository,
+        remote: &mut Remote,
+        refspecs: &[&str],
+        download_tags: bool,
+        max_retries: u32,
+    ) -> Result<u32> {
+        let mut retries = 0;
+        loop {
+            match self.fetch(repo, remote, refspecs, download_tags) {
+                Ok(()) => return Ok(retries),
+                Err(e) if retries < max_retries && is_transient_fetch_error(&e) => {
+                    retries += 1;
+                    std::thread::sleep(Duration::from_secs(1 << (retries - 1).min(4)));
+                }
+                Err(e) if retries > 0 => {
+                    return Err(anyhow!("{} (gave up after {} retries)", e, retries));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Network, TLS and OS-level failures are worth retrying; auth failures and
+/// everything else are not
+fn is_transient_fetch_error(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<git2::Error>() else {
+        return false;
+    };
+    error.code() != git2::ErrorCode::Auth
+        && matches!(
+            error.class(),
+            git2::ErrorClass::Net | git2::ErrorClass::Ssl | git2::ErrorClass::Os | git2::ErrorClass::Http
+        )
+}
+
+impl RepoCommand {
+    /// Whether the command writes machine-readable output that decoration would corrupt
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, RepoCommand::List(args) if args.json || args.json_lines)
+            || matches!(self, RepoCommand::Status(args) if args.json)
+            || matches!(self, RepoCommand::Open(args) if args.print)
+    }
+}
+
 pub async fn handle_command(cmd: RepoCommand) -> Result<()> {
     match cmd {
-        RepoCommand::List { public_only } => list_repositories(public_only).await,
-        RepoCommand::Clone { repo, path, ssh } => clone_repository(&repo, path, ssh).await,
-        RepoCommand::Update { dry_run } => update_repositories(dry_run).await,
-        RepoCommand::Status => check_repository_status().await,
+        RepoCommand::List(args) => list_repositories(&args).await,
+        RepoCommand::Clone(args) => clone_repository(&args).await,
+        RepoCommand::CloneAll(args) => clone_all_repositories(&args).await,
+        RepoCommand::Update(args) => update_repositories(args).await,
+        RepoCommand::Status(args) => check_repository_status(&args).await,
+        RepoCommand::BlameOrg(args) => show_last_committers(&args),
+        RepoCommand::Rename(args) => rename_checkout(&args).await,
+        RepoCommand::Open(args) => open_repository_page(&args),
+        RepoCommand::PruneBranches(args) => prune_branches(&args),
+        RepoCommand::SetRemote { url, ssh } => set_remote_url(url, ssh),
     }
 }
 
-async fn list_repositories(public_only: bool) -> Result<()> {
-    println!("📋 Fetching repositories from {}...", style(GITHUB_ORG).cyan().bold());
+async fn list_repositories(args: &ListArgs) -> Result<()> {
+    let host = GitHubHost::resolve(&args.host)?;
+    if args.json || args.json_lines {
+        // stdout is reserved for the JSON, so progress goes to stderr
+        eprintln!("Fetching repositories from {}...", host.org);
+    } else {
+        statusln!("📋 Fetching repositories from {}...", style(&host.org).cyan().bold());
+    }
 
     let client = reqwest::Client::new();
-    let url = format!("{}/orgs/{}/repos?per_page=100&type=all", GITHUB_API_BASE, GITHUB_ORG);
-    
-    let response = client
-        .get(&url)
-        .header("User-Agent", "fbcli")
-        .send()
-        .await
-        .context("Failed to fetch repositories from GitHub")?;
+    let token = github_token();
 
-    if !response.status().is_success() {
-        return Err(anyhow!("GitHub API request failed: {}", response.status()));
+    // A repository counts as cloned when a git checkout with its name sits in the current directory
+    let current_dir = current_dir()?;
+    let is_cloned = |repo: &GitHubRepo| is_git_repository(&current_dir.join(&repo.name));
+    let is_listed = |repo: &GitHubRepo| (!args.public_only || !repo.private) && (!args.missing_only || !is_cloned(repo));
+
+    // The listing already carries every default branch; keep them for `repo status --verify-remote`
+    let mut branch_cache = DefaultBranchCache::load();
+    let mut remember_default_branch = |repo: &GitHubRepo| {
+        if let Some((owner, name)) = repo.full_name.split_once('/') {
+            branch_cache.insert(&default_branch_key(&host.git_host, owner, name), &repo.default_branch);
+        }
+    };
+
+    if args.json_lines {
+        // Each page is written as it arrives, so consumers can start before the listing ends
+        let mut stdout = std::io::stdout().lock();
+        for_each_org_repos_page(&client, &host, token.as_deref(), |page| {
+            for repo in page.iter().inspect(|repo| remember_default_branch(repo)).filter(|repo| is_listed(repo)) {
+                // Written raw: plain-mode rendering would alter emoji inside descriptions
+                writeln!(stdout, "{}", serde_json::to_string(repo)?)?;
+            }
+            stdout.flush()?;
+            Ok(())
+        })
+        .await?;
+        branch_cache.save();
+        return Ok(());
     }
 
-    let repos: Vec<GitHubRepo> = response
-        .json()
-        .await
-        .context("Failed to parse GitHub API response")?;
+    let repos = fetch_org_repos(&client, &host, token.as_deref()).await?;
+    repos.iter().for_each(&mut remember_default_branch);
+    branch_cache.save();
 
-    let filtered_repos: Vec<&GitHubRepo> = repos
-        .iter()
-        .filter(|repo| !public_only || !repo.private)
-        .collect();
+    let mut filtered_repos: Vec<&GitHubRepo> = repos.iter().filter(|repo| is_listed(repo)).collect();
+
+    if args.json {
+        // Written raw: plain-mode rendering would alter emoji inside descriptions
+        println!("{}", serde_json::to_string_pretty(&filtered_repos)?);
+        return Ok(());
+    }
+
+    outln!();
+    if args.missing_only {
+        outln!("{} Found {} repositories not cloned in {}:", "📦".bold(), filtered_repos.len(), current_dir.display());
+    } else {
+        outln!("{} Found {} repositories:", "📦".bold(), filtered_repos.len());
+    }
+    if token.is_none() && !args.public_only {
+        outln!("{}", "ℹ Private repositories are only listed when FBCLI_GITHUB_TOKEN or GITHUB_TOKEN is set".dimmed());
+    }
+    outln!();
 
-    println!();
-    println!("{} Found {} repositories:", "📦".bold(), filtered_repos.len());
-    println!();
+    if args.recent {
+        // RFC 3339 timestamps in UTC sort chronologically as strings
+        filtered_repos.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        let now = Utc::now();
+        let rows: Vec<Vec<String>> = filtered_repos
+            .iter()
+            .map(|repo| {
+                let updated = DateTime::parse_from_rfc3339(&repo.updated_at)
+                    .map(|t| format_relative_time(t.with_timezone(&Utc), now))
+                    .unwrap_or_else(|_| repo.updated_at.clone());
+                let visibility = if repo.private { "private" } else { "public" };
+                let mut row = vec![repo.name.clone(), updated, visibility.to_string()];
+                if args.compare_local {
+                    row.push(if is_cloned(repo) { "cloned" } else { "missing" }.to_string());
+                }
+                row
+            })
+            .collect();
+        let mut headers = vec!["Repository", "Updated", "Visibility"];
+        if args.compare_local {
+            headers.push("Local");
+        }
+        outln!("{}", render_table(&headers, &rows));
+        return Ok(());
+    }
 
     for repo in filtered_repos {
         let visibility = if repo.private { "🔒 Private" } else { "🌐 Public" };
         let description = repo.description.as_deref().unwrap_or("No description");
         
-        println!("{} {}", "▶".bright_blue(), style(&repo.name).cyan().bold());
-        println!("  {} {}", visibility, style(description).dim());
-        println!("  🔗 {}", style(&repo.html_url).blue().underlined());
-        println!();
+        outln!("{} {}", "▶".bright_blue(), style(&repo.name).cyan().bold());
+        outln!("  {} {}", visibility, style(description).dim());
+        outln!("  🔗 {}", style(&repo.html_url).blue().underlined());
+        if args.compare_local {
+            if is_cloned(repo) {
+                outln!("  ✅ Cloned locally");
+            } else {
+                outln!("  ⬇️  Not cloned");
+            }
+        }
+        outln!();
     }
 
     Ok(())
 }
 
-async fn clone_repository(repo_name: &str, target_path: Option<PathBuf>, use_ssh: bool) -> Result<()> {
-    let target_dir = target_path.unwrap_or_else(|| PathBuf::from(repo_name));
+/// Fetch the organization's repositories from the given GitHub instance.
+/// Private repositories are only included when a token with access is given.
+async fn fetch_org_repos(client: &reqwest::Client, host: &GitHubHost, token: Option<&str>) -> Result<Vec<GitHubRepo>> {
+    github::get_all_pages(&org_repos_url(host), |url| github_get(client, url, token))
+        .await
+        .with_context(|| format!("Failed to fetch repositories from {}", host.api_base))
+}
+
+/// First page of the organization's repository listing
+fn org_repos_url(host: &GitHubHost) -> String {
+    format!("{}/orgs/{}/repos?per_page=100&type=all", host.api_base, host.org)
+}
+
+/// Like `fetch_org_repos`, but hands over each page of repositories as it arrives
+async fn for_each_org_repos_page(
+    client: &reqwest::Client,
+    host: &GitHubHost,
+    token: Option<&str>,
+    on_page: impl FnMut(Vec<GitHubRepo>) -> Result<()>,
+) -> Result<()> {
+    github::for_each_page(&org_repos_url(host), |url| github_get(client, url, token), on_page)
+        .await
+        .with_context(|| format!("Failed to fetch repositories from {}", host.api_base))
+}
+
+async fn clone_repository(args: &CloneArgs) -> Result<()> {
+    let repo_name = args.repo.as_str();
+    let target_dir = args.path.clone().unwrap_or_else(|| PathBuf::from(repo_name));
     
-    println!("📥 Cloning repository: {}", style(repo_name).cyan().bold());
-    println!("📂 Target directory: {}", style(target_dir.display()).yellow());
+    outln!("📥 Cloning repository: {}", style(repo_name).cyan().bold());
+    statusln!("📂 Target directory: {}", style(target_dir.display()).yellow());
 
     if target_dir.exists() {
         return Err(anyhow!("Directory '{}' already exists", target_dir.display()));
     }
 
-    let repo_url = if use_ssh {
-        format!("git@github.com:{}/{}.git", GITHUB_ORG, repo_name)
-    } else {
-        format!("https://github.com/{}/{}.git", GITHUB_ORG, repo_name)
-    };
+    if args.partial {
+        let version = git_version()?;
+        if version < MIN_PARTIAL_CLONE_GIT {
+            let (major, minor, patch) = MIN_PARTIAL_CLONE_GIT;
+            return Err(anyhow!(
+                "--partial requires git {}.{}.{} or newer (found {}.{}.{})",
+                major, minor, patch, version.0, version.1, version.2
+            ));
+        }
+    }
+
+    let host = GitHubHost::resolve(&args.host)?;
+    if !args.no_verify {
+        verify_repository_exists(&host, repo_name).await?;
+    }
+
+    let repo_url = host.repo_url(&host.org, repo_name, args.ssh);
 
-    println!("🔗 Repository URL: {}", style(&repo_url).blue());
+    statusln!("🔗 Repository URL: {}", style(&repo_url).blue());
 
     // Create progress bar
     let pb = ProgressBar::new_spinner();
@@ -131,25 +749,55 @@ async fn clone_repository(repo_name: &str, target_path: Option<PathBuf>, use_ssh
             .template("{spinner:.green} {msg}")
             .unwrap()
     );
+    if !ui::shows_progress() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
     pb.set_message("Cloning repository...");
 
-    // Clone the repository
-    let result = Repository::clone(&repo_url, &target_dir);
+    // Clone the repository. git2 can't do partial clones, so those go through the git CLI.
+    let branch = args.branch.as_deref();
+    let result = if args.partial {
+        partial_clone(&repo_url, &target_dir, branch)
+    } else {
+        CredentialCache::default().clone_repo(&repo_url, &target_dir, branch).map(|_| ())
+    };
     pb.finish_and_clear();
 
+    // A clone that fails after fetching leaves the directory behind
+    if result.is_err() && target_dir.exists() {
+        let _ = fs::remove_dir_all(&target_dir);
+    }
+    if let (Some(branch), Err(e)) = (branch, &result) {
+        if is_missing_branch_error(e) {
+            return Err(anyhow!("Branch '{}' does not exist in {}/{}", branch, host.org, repo_name));
+        }
+    }
+
     match result {
         Ok(_) => {
-            println!("{}", "✅ Repository cloned successfully!".green().bold());
-            println!("📁 Location: {}", style(target_dir.display()).yellow());
+            outln!("{}", "✅ Repository cloned successfully!".green().bold());
+            outln!("📁 Location: {}", style(target_dir.display()).yellow());
+            let checked_out = Repository::open(&target_dir)
+                .ok()
+                .and_then(|repo| repo.head().ok().and_then(|head| head.shorthand().map(str::to_string)));
+            if let Some(checked_out) = &checked_out {
+                outln!("🌿 Branch: {}", style(checked_out).green());
+            }
+            if let Ok(size) = dir_size(&target_dir) {
+                outln!("💾 Size on disk: {}", style(format_bytes(size)).yellow());
+            }
             
             // Show next steps
-            println!();
-            println!("{}", "Next steps:".bold());
-            println!("  cd {}", repo_name);
+            outln!();
+            outln!("{}", "Next steps:".bold());
+            outln!("  cd {}", repo_name);
+            if let Some(checked_out) = checked_out.filter(|_| branch.is_some()) {
+                outln!("  git pull   # keeps '{}' up to date; repo update also follows it", checked_out);
+            }
             
             // Check if it's a Rust project
             if target_dir.join("Cargo.toml").exists() {
-                println!("  cargo build");
+                outln!("  cargo build");
             }
         },
         Err(e) => {
@@ -160,222 +808,1913 @@ async fn clone_repository(repo_name: &str, target_path: Option<PathBuf>, use_ssh
     Ok(())
 }
 
-async fn update_repositories(dry_run: bool) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
-    
-    println!("🔄 Scanning for Far-Beyond repositories in: {}", style(current_dir.display()).yellow());
-    
-    let mut repos_found = Vec::new();
-    
-    // Scan for git repositories
-    for entry in fs::read_dir(&current_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            let git_dir = path.join(".git");
-            if git_dir.exists() {
-                // Check if it's a Far-Beyond repository
-                if let Ok(repo) = Repository::open(&path) {
-                    if let Ok(remote) = repo.find_remote("origin") {
-                        if let Some(url) = remote.url() {
-                            if url.contains("Far-Beyond-Dev") || url.contains("far-beyond-dev") {
-                                repos_found.push(path);
-                            }
-                        }
+/// Default for `repo clone-all --jobs`; kept low so a full org clone doesn't hammer GitHub
+const DEFAULT_CLONE_ALL_JOBS: u32 = 3;
+
+async fn clone_all_repositories(args: &CloneAllArgs) -> Result<()> {
+    let host = GitHubHost::resolve(&args.host)?;
+    statusln!("📋 Fetching repositories from {}...", style(&host.org).cyan().bold());
+
+    let client = reqwest::Client::new();
+    let token = github_token();
+    let repos: Vec<GitHubRepo> = fetch_org_repos(&client, &host, token.as_deref())
+        .await?
+        .into_iter()
+        .filter(|repo| !args.public_only || !repo.private)
+        .collect();
+
+    let current_dir = current_dir()?;
+    outln!("📦 Found {} repositories; cloning into {}", repos.len(), style(current_dir.display()).yellow());
+    outln!();
+
+    // Clone concurrently, then report in listing order
+    let limit = Arc::new(Semaphore::new(args.jobs as usize));
+    let credentials = CredentialCache::default();
+    let tasks: Vec<_> = repos.iter()
+        .map(|repo| {
+            let target_dir = current_dir.join(&repo.name);
+            if target_dir.exists() {
+                return None;
+            }
+            let url = host.repo_url(&host.org, &repo.name, args.ssh);
+            let limit = limit.clone();
+            let credentials = credentials.clone();
+            Some(tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await?;
+                tokio::task::spawn_blocking(move || {
+                    let result = credentials.clone_repo(&url, &target_dir, None).map(|_| ());
+                    // A clone that fails after fetching leaves the directory behind
+                    if result.is_err() && target_dir.exists() {
+                        let _ = fs::remove_dir_all(&target_dir);
                     }
-                }
+                    result
+                }).await?
+            }))
+        })
+        .collect();
+
+    let mut summary = BatchSummary::new("repo clone-all");
+    for (repo, task) in repos.iter().zip(tasks) {
+        let target_dir = current_dir.join(&repo.name);
+        out!("  Cloning {}... ", style(&repo.name).cyan());
+        let Some(task) = task else {
+            outln!("{}", "📋 Skipped (exists)".blue());
+            summary.push(ItemOutcome::new(&target_dir, OutcomeStatus::Skipped).with_message("directory exists"));
+            continue;
+        };
+        match task.await.map_err(anyhow::Error::from).and_then(|result| result) {
+            Ok(()) => {
+                outln!("{}", "✅ Cloned".green());
+                summary.push(ItemOutcome::new(&target_dir, OutcomeStatus::Success));
+            }
+            Err(e) => {
+                outln!("{} {}", "❌ Failed:".red(), e);
+                summary.push(ItemOutcome::new(&target_dir, OutcomeStatus::Failed).with_message(e.to_string()));
             }
         }
     }
 
-    if repos_found.is_empty() {
-        println!("❌ No Far-Beyond repositories found in current directory");
-        return Ok(());
-    }
+    outln!();
+    let counts = &summary.counts;
+    outln!(
+        "{}",
+        format!("✅ Cloned {}, skipped {}, failed {}", counts.success, counts.skipped, counts.failed).green().bold()
+    );
+    summary.write_if_requested(args.summary_json.as_deref())
+}
 
-    println!("📦 Found {} Far-Beyond repositories:", repos_found.len());
-    
-    for repo_path in &repos_found {
-        let repo_name = repo_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-        println!("  • {}", style(repo_name).cyan());
+/// Blobless clone through the git CLI
+fn partial_clone(url: &str, target_dir: &Path, branch: Option<&str>) -> Result<()> {
+    let mut command = std::process::Command::new("git");
+    command.args(["clone", "--filter=blob:none", "--quiet"]);
+    if let Some(branch) = branch {
+        command.args(["--branch", branch]);
     }
-    
-    if dry_run {
-        println!();
-        println!("{}", "🔍 Dry run mode - no changes will be made".yellow().bold());
-        return Ok(());
+    let output = command.arg(url).arg(target_dir).output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()));
     }
+    Ok(())
+}
 
-    println!();
-    println!("🔄 Updating repositories...");
+/// Whether a clone failed because the requested branch isn't on the remote
+fn is_missing_branch_error(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<git2::Error>() {
+        // git2 fetches everything, then can't find refs/remotes/origin/<branch>
+        Some(error) => error.code() == git2::ErrorCode::NotFound,
+        // git CLI (partial clones): "Remote branch <branch> not found in upstream origin"
+        None => error.to_string().contains("not found in upstream"),
+    }
+}
 
-    for repo_path in repos_found {
-        let repo_name = repo_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-        
-        print!("  Updating {}... ", style(repo_name).cyan());
-        
-        match update_single_repository(&repo_path) {
-            Ok(updated) => {
-                if updated {
-                    println!("{}", "✅ Updated".green());
-                } else {
-                    println!("{}", "📋 Already up to date".blue());
-                }
-            },
-            Err(e) => {
-                println!("{} {}", "❌ Failed:".red(), e);
+/// Scan a directory for Far-Beyond repositories, applying the given filters
+fn scan_far_beyond_repos(dir: &Path, scan: &ScanArgs) -> Result<Vec<PathBuf>> {
+    let org = GitHubHost::resolve(&GitHubHostArgs::default())?.org;
+    let mut repos_found = Vec::new();
+
+    for path in scan_candidates(dir, scan.recursive)? {
+        match org_membership(&path, &org) {
+            OrgMembership::Owned => {}
+            OrgMembership::Fork(_) if scan.include_forks => {}
+            OrgMembership::Fork(origin) => {
+                outln!(
+                    "⚠️  Skipping {}: origin {} is not owned by {} (use --include-forks to include it)",
+                    path.file_name().unwrap_or_default().to_string_lossy(), origin, org
+                );
+                continue;
             }
+            OrgMembership::Unrelated => continue,
+        }
+        if scan.only_rust && !is_rust_project(&path) {
+            continue;
         }
+        repos_found.push(path);
     }
 
-    println!();
-    println!("{}", "✅ Repository update complete!".green().bold());
-    Ok(())
+    repos_found.sort();
+    Ok(repos_found)
 }
 
-fn update_single_repository(repo_path: &Path) -> Result<bool> {
-    let repo = Repository::open(repo_path)?;    // Fetch from origin
-    let mut remote = repo.find_remote("origin")?;
-    let refspecs: &[&str] = &[];
-    remote.fetch(refspecs, None, None)?;
-    
-    // Get current branch
-    let head = repo.head()?;
-    let local_oid = head.target().unwrap();
-    
-    // Get remote branch
-    let branch_name = head.shorthand().unwrap_or("main");
-    let remote_branch_name = format!("origin/{}", branch_name);
-    let remote_ref = repo.find_reference(&format!("refs/remotes/{}", remote_branch_name))?;
-    let remote_oid = remote_ref.target().unwrap();
-    
-    // Check if update is needed
-    if local_oid == remote_oid {
-        return Ok(false); // Already up to date
+/// Git checkouts directly in `dir`, or below it at any depth when `recursive`
+fn scan_candidates(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    if recursive {
+        return find_git_repositories(dir);
     }
-    
-    // Perform fast-forward merge
-    let (analysis, _) = repo.merge_analysis(&[&repo.find_annotated_commit(remote_oid)?])?;
-    
-    if analysis.is_fast_forward() {
-        // Update the reference
-        let mut reference = repo.find_reference(&format!("refs/heads/{}", branch_name))?;
-        reference.set_target(remote_oid, "Fast-forward")?;
-        
-        // Update working directory
-        repo.set_head(&format!("refs/heads/{}", branch_name))?;
-        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
-        
-        Ok(true)
-    } else {
-        Err(anyhow!("Cannot fast-forward, manual merge required"))
+    let mut repos = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && is_git_repository(&path) {
+            repos.push(path);
+        }
     }
+    Ok(repos)
 }
 
-async fn check_repository_status() -> Result<()> {
-    let current_dir = std::env::current_dir()?;
-    
-    println!("📊 Checking status of Far-Beyond repositories...");
-    println!("📂 Scanning directory: {}", style(current_dir.display()).yellow());
-    println!();
+/// Directories that never hold checkouts worth scanning
+const SKIPPED_SCAN_DIRS: &[&str] = &[".git", "target", "node_modules"];
 
-    let mut repos_found = 0;
-    
-    for entry in fs::read_dir(&current_dir)? {
+/// Every git checkout below `dir` at any depth, for `--recursive`. Checkouts
+/// aren't descended into, so submodules and nested clones are left alone.
+fn find_git_repositories(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut repos = Vec::new();
+    let mut walker = WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.file_type().is_dir()
+                && !SKIPPED_SCAN_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+        });
+    while let Some(entry) = walker.next() {
         let entry = entry?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            let git_dir = path.join(".git");
-            if git_dir.exists() {
-                if let Ok(repo) = Repository::open(&path) {
-                    if let Ok(remote) = repo.find_remote("origin") {
-                        if let Some(url) = remote.url() {
-                            if url.contains("Far-Beyond-Dev") || url.contains("far-beyond-dev") {
-                                repos_found += 1;
-                                show_repository_status(&path, &repo)?;
-                                println!();
-                            }
-                        }
-                    }
-                }
-            }
+        if is_git_repository(entry.path()) {
+            repos.push(entry.into_path());
+            walker.skip_current_dir();
         }
     }
+    Ok(repos)
+}
 
-    if repos_found == 0 {
-        println!("❌ No Far-Beyond repositories found in current directory");
+/// Owner parsed from the repository's origin URL, or "unknown"
+fn origin_owner(path: &Path) -> String {
+    Repository::open(path).ok()
+        .and_then(|repo| repo.find_remote("origin").ok()?.url().and_then(parse_github_remote))
+        .map(|remote| remote.owner)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// How a checkout relates to the organization, judged by its remotes
+enum OrgMembership {
+    /// origin's owner is the organization
+    Owned,
+    /// origin points elsewhere, but the checkout looks like it came from the
+    /// organization: another remote (e.g. `upstream`) is owned by it, or the
+    /// origin URL mentions it. Holds the origin URL.
+    Fork(String),
+    Unrelated,
+}
+
+fn org_membership(path: &Path, org: &str) -> OrgMembership {
+    let Ok(repo) = Repository::open(path) else {
+        return OrgMembership::Unrelated;
+    };
+    let owned_by_org = |url: &str| parse_github_remote(url).is_some_and(|remote| remote.owner.eq_ignore_ascii_case(org));
+    let remote_url = |name: &str| repo.find_remote(name).ok().and_then(|remote| remote.url().map(str::to_string));
+
+    let Some(origin) = remote_url("origin") else {
+        return OrgMembership::Unrelated;
+    };
+    if owned_by_org(&origin) {
+        return OrgMembership::Owned;
+    }
+    let other_remote_owned = repo.remotes().ok().is_some_and(|names| {
+        names.iter().flatten().filter(|name| *name != "origin").any(|name| remote_url(name).is_some_and(|url| owned_by_org(&url)))
+    });
+    if other_remote_owned || origin.to_lowercase().contains(&org.to_lowercase()) {
+        OrgMembership::Fork(origin)
     } else {
-        println!("📈 Status check complete for {} repositories", repos_found);
+        OrgMembership::Unrelated
     }
-
-    Ok(())
 }
 
-fn show_repository_status(repo_path: &Path, repo: &Repository) -> Result<()> {
-    let repo_name = repo_path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
-    
-    println!("{} {}", "📦".bold(), style(repo_name).cyan().bold());
-    
-    // Current branch
-    if let Ok(head) = repo.head() {
-        if let Some(branch_name) = head.shorthand() {
-            println!("  🌿 Branch: {}", style(branch_name).green());
+fn show_last_committers(args: &BlameOrgArgs) -> Result<()> {
+    let current_dir = current_dir()?;
+
+    statusln!("🔍 Finding the last committer of each Far-Beyond repository...");
+    statusln!("📂 Scanning directory: {}", style(current_dir.display()).yellow());
+    outln!();
+
+    let mut entries = Vec::new();
+    for path in scan_far_beyond_repos(&current_dir, &args.scan)? {
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let last = Repository::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|repo| last_default_branch_commit(&repo));
+        match last {
+            Ok(commit) => entries.push((name, commit)),
+            Err(e) => outln!("⚠️  {}: {}", name, e),
         }
     }
-    
-    // Check for uncommitted changes
-    let statuses = repo.statuses(None)?;
-    let mut changes = Vec::new();
-    
-    for status in statuses.iter() {
-        let flags = status.status();
-        if flags.contains(git2::Status::WT_MODIFIED) {
-            changes.push("modified");
+
+    if entries.is_empty() {
+        outln!("❌ No Far-Beyond repositories found in current directory");
+        return Ok(());
+    }
+
+    // Most recently touched first, so stale repositories sink to the bottom
+    entries.sort_by_key(|(_, commit)| std::cmp::Reverse(commit.time));
+    let now = Utc::now();
+    let rows: Vec<Vec<String>> = entries
+        .into_iter()
+        .map(|(name, commit)| vec![
+            name,
+            commit.branch,
+            commit.author,
+            commit.email,
+            format_relative_time(commit.time, now),
+        ])
+        .collect();
+    outln!("{}", render_table(&["Repository", "Branch", "Author", "Email", "Last commit"], &rows));
+
+    Ok(())
+}
+
+/// Author details of the newest commit on a repository's default branch
+struct LastCommit {
+    branch: String,
+    author: String,
+    email: String,
+    time: DateTime<Utc>,
+}
+
+/// Read the tip of the default branch: `origin/HEAD` when the clone recorded
+/// it, otherwise whatever is checked out
+fn last_default_branch_commit(repo: &Repository) -> Result<LastCommit> {
+    let reference = match repo.find_reference("refs/remotes/origin/HEAD") {
+        Ok(origin_head) => origin_head.resolve()?,
+        Err(_) => repo.head()?,
+    };
+    let branch = reference.shorthand().unwrap_or("HEAD").to_string();
+    let commit = reference.peel_to_commit()?;
+    let author = commit.author();
+    let time = DateTime::from_timestamp(commit.time().seconds(), 0)
+        .ok_or_else(|| anyhow!("commit {} has an invalid timestamp", short_oid(commit.id())))?;
+
+    Ok(LastCommit {
+        branch,
+        author: author.name().unwrap_or("unknown").to_string(),
+        email: author.email().unwrap_or("").to_string(),
+        time,
+    })
+}
+
+async fn rename_checkout(args: &RenameArgs) -> Result<()> {
+    // Read before the rename: afterwards getcwd returns the new path or fails
+    let cwd = current_dir()?;
+    let cwd = fs::canonicalize(&cwd).unwrap_or(cwd);
+    let path = args.path.clone().unwrap_or_else(|| PathBuf::from("."));
+    let path = fs::canonicalize(&path)
+        .with_context(|| format!("Cannot access {}", path.display()))?;
+    let repo = Repository::open(&path)
+        .with_context(|| format!("{} is not a git repository", path.display()))?;
+    let old_url = repo.find_remote("origin")
+        .context("Repository has no 'origin' remote")?
+        .url()
+        .ok_or_else(|| anyhow!("origin remote URL is not valid UTF-8"))?
+        .to_string();
+    let remote = parse_github_remote(&old_url)
+        .ok_or_else(|| anyhow!("origin is not a GitHub remote: {}", old_url))?;
+
+    statusln!("🔍 Looking up {}/{} on GitHub...", remote.owner, remote.repo);
+    let client = reqwest::Client::new();
+    let host = GitHubHost::resolve(&GitHubHostArgs::default())?;
+    let info = fetch_repo_info(&client, &host, &remote, github_token().as_deref()).await?;
+
+    let current_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Cannot rename {}", path.display()))?;
+    let target = path.with_file_name(&info.name);
+
+    let (new_owner, _) = info.full_name.split_once('/').unwrap_or((&remote.owner, &info.name));
+    let moved_upstream = new_owner != remote.owner || info.name != remote.repo;
+    let new_url = moved_upstream.then(|| {
+        let renamed = GitHubRemote {
+            host: remote.host.clone(),
+            owner: new_owner.to_string(),
+            repo: info.name.clone(),
+        };
+        if old_url.starts_with("http") { renamed.https_url() } else { renamed.ssh_url() }
+    });
+
+    let rename_dir = current_name != info.name;
+    let update_remote = args.update_remote && new_url.is_some();
+    if !rename_dir && !update_remote {
+        outln!("📋 {} already matches {}", current_name, style(&info.full_name).cyan());
+        if let Some(new_url) = &new_url {
+            outln!("  origin still uses the old URL; pass --update-remote to change it to {}", new_url);
         }
-        if flags.contains(git2::Status::WT_NEW) {
-            changes.push("untracked");
+        return Ok(());
+    }
+    if rename_dir && target.exists() {
+        return Err(anyhow!("Cannot rename to {}: it already exists", target.display()));
+    }
+
+    if rename_dir {
+        outln!("📂 {} -> {}", path.display(), style(target.display()).yellow());
+    }
+    if let (true, Some(new_url)) = (update_remote, &new_url) {
+        outln!("🔗 origin: {} -> {}", style(&old_url).dim(), style(new_url).blue());
+    }
+    if !args.yes && !confirm("Proceed?")? {
+        outln!("❌ Aborted");
+        return Ok(());
+    }
+
+    if let (true, Some(new_url)) = (update_remote, &new_url) {
+        repo.remote_set_url("origin", new_url)?;
+    }
+    drop(repo);
+    if rename_dir {
+        fs::rename(&path, &target)
+            .with_context(|| format!("Failed to rename {} to {}", path.display(), target.display()))?;
+    }
+
+    outln!("{}", "✅ Checkout renamed".green().bold());
+    if rename_dir && cwd.starts_with(&path) {
+        outln!("  Your shell is still in the old path; run: cd {}", target.display());
+    }
+    if !update_remote {
+        if let Some(new_url) = &new_url {
+            outln!("  origin still uses the old URL; pass --update-remote to change it to {}", new_url);
         }
-        if flags.contains(git2::Status::INDEX_NEW) {
-            changes.push("staged");
+    }
+    Ok(())
+}
+
+fn open_repository_page(args: &OpenArgs) -> Result<()> {
+    let remote = match &args.repo {
+        Some(name) => {
+            let host = GitHubHost::resolve(&GitHubHostArgs::default())?;
+            GitHubRemote { host: host.git_host, owner: host.org, repo: name.clone() }
         }
+        None => {
+            let repo = Repository::discover(current_dir()?)
+                .context("Not inside a git repository; pass a repository name")?;
+            let origin = repo.find_remote("origin")
+                .context("Repository has no 'origin' remote")?;
+            let url = origin.url()
+                .ok_or_else(|| anyhow!("origin remote URL is not valid UTF-8"))?;
+            parse_github_remote(url)
+                .ok_or_else(|| anyhow!("origin is not a GitHub remote: {}", url))?
+        }
+    };
+
+    let mut url = remote.web_url();
+    if args.pulls {
+        url.push_str("/pulls");
+    } else if args.issues {
+        url.push_str("/issues");
     }
-    
-    if changes.is_empty() {
-        println!("  ✅ Working directory clean");
+
+    if args.print {
+        // Raw so it can be captured by scripts
+        println!("{}", url);
+        return Ok(());
+    }
+    outln!("🌐 Opening {}", style(&url).blue().underlined());
+    open_in_browser(&url)
+}
+
+fn set_remote_url(url: Option<String>, use_ssh: bool) -> Result<()> {
+    let current_dir = current_dir()?;
+    let repo = Repository::discover(&current_dir)
+        .context("Not inside a git repository")?;
+    let remote = repo.find_remote("origin")
+        .context("Repository has no 'origin' remote")?;
+    let old_url = remote.url()
+        .ok_or_else(|| anyhow!("origin remote URL is not valid UTF-8"))?
+        .to_string();
+
+    let new_url = match url {
+        Some(url) => url,
+        None => {
+            let parsed = parse_github_remote(&old_url)
+                .ok_or_else(|| anyhow!("Cannot convert '{}'; pass the new URL explicitly", old_url))?;
+            if use_ssh { parsed.ssh_url() } else { parsed.https_url() }
+        }
+    };
+
+    if new_url == old_url {
+        outln!("📋 origin already points to {}", style(&old_url).blue());
+        return Ok(());
+    }
+
+    repo.remote_set_url("origin", &new_url)?;
+
+    outln!("{}", "✅ Updated origin remote".green().bold());
+    outln!("  Old: {}", style(&old_url).dim());
+    outln!("  New: {}", style(&new_url).blue());
+    Ok(())
+}
+
+/// A local branch `repo prune-branches` would delete
+struct PrunableBranch {
+    name: String,
+    tip: Oid,
+    /// Fully merged into the default branch
+    merged: bool,
+    /// Has an upstream configured whose remote-tracking branch no longer exists
+    upstream_gone: bool,
+}
+
+fn prune_branches(args: &PruneBranchesArgs) -> Result<()> {
+    let current_dir = current_dir()?;
+    let repo_paths = if args.all {
+        outln!("🔍 Looking for branches to prune in: {}", style(current_dir.display()).yellow());
+        scan_far_beyond_repos(&current_dir, &args.scan)?
     } else {
-        println!("  ⚠️  Uncommitted changes: {}", changes.join(", "));
+        let repo = Repository::discover(&current_dir)
+            .context("Not inside a git repository")?;
+        vec![repo.workdir().unwrap_or(repo.path()).to_path_buf()]
+    };
+
+    let mut to_delete = Vec::new();
+    for path in &repo_paths {
+        let repo_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let found = Repository::open(path).map_err(anyhow::Error::from).and_then(|repo| {
+            let default_branch = local_default_branch(&repo)?;
+            Ok((find_prunable_branches(&repo, &default_branch)?, default_branch))
+        });
+        let (branches, default_branch) = match found {
+            Ok(found) => found,
+            Err(e) => {
+                outln!("{} {} {} {}", "📦".bold(), style(repo_name).cyan().bold(), "❌ Failed:".red(), e);
+                continue;
+            }
+        };
+        if branches.is_empty() {
+            continue;
+        }
+
+        outln!("{} {} (default branch: {})", "📦".bold(), style(repo_name).cyan().bold(), default_branch);
+        for branch in branches {
+            let reason = match (branch.merged, branch.upstream_gone) {
+                (true, false) => format!("merged into {}", default_branch),
+                (true, true) => format!("upstream gone, merged into {}", default_branch),
+                (false, _) => "upstream gone, not merged".to_string(),
+            };
+            if branch.merged || args.force {
+                outln!("  • {} ({})", style(&branch.name).green(), reason);
+                to_delete.push((path.clone(), branch));
+            } else {
+                outln!("  • {} ({}) {}", style(&branch.name).yellow(), reason, "- kept, use --force to delete".dimmed());
+            }
+        }
+    }
+
+    if to_delete.is_empty() {
+        outln!("✅ No branches to prune");
+        return Ok(());
+    }
+    if args.dry_run {
+        outln!();
+        outln!("{}", format!("🔍 Dry run: {} branches would be deleted", to_delete.len()).yellow().bold());
+        return Ok(());
+    }
+    outln!();
+    if !args.yes && !confirm(&format!("Delete {} branches?", to_delete.len()))? {
+        outln!("❌ Aborted");
+        return Ok(());
+    }
+
+    for (path, branch) in &to_delete {
+        let deleted = Repository::open(path)
+            .and_then(|repo| repo.find_branch(&branch.name, BranchType::Local)?.delete());
+        let repo_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+        match deleted {
+            Ok(()) => outln!("  ✅ Deleted {}/{} (was {})", repo_name, branch.name, short_oid(branch.tip)),
+            Err(e) => outln!("  ❌ Failed to delete {}/{}: {}", repo_name, branch.name, e.message()),
+        }
+    }
+    Ok(())
+}
+
+/// The default branch as origin last reported it (`origin/HEAD`), falling back to `main` or `master`
+fn local_default_branch(repo: &Repository) -> Result<String> {
+    let origin_head = repo.find_reference("refs/remotes/origin/HEAD").ok()
+        .and_then(|head| head.symbolic_target().map(str::to_string))
+        .and_then(|target| target.strip_prefix("refs/remotes/origin/").map(str::to_string));
+    if let Some(branch) = origin_head {
+        return Ok(branch);
     }
+    ["main", "master"].iter()
+        .find(|name| {
+            repo.find_branch(name, BranchType::Local).is_ok()
+                || repo.find_branch(&format!("origin/{}", name), BranchType::Remote).is_ok()
+        })
+        .map(|name| name.to_string())
+        .ok_or_else(|| anyhow!("Cannot tell the default branch (no origin/HEAD, main or master)"))
+}
+
+/// Local branches that are merged into `default_branch` (as of `origin/<default>`
+/// when it exists) or whose upstream is gone. The current and default branches are never included.
+fn find_prunable_branches(repo: &Repository, default_branch: &str) -> Result<Vec<PrunableBranch>> {
+    let base = repo.refname_to_id(&format!("refs/remotes/origin/{}", default_branch))
+        .or_else(|_| repo.refname_to_id(&format!("refs/heads/{}", default_branch)))
+        .with_context(|| format!("Default branch {} not found", default_branch))?;
+    let current = repo.head().ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+
+    let mut prunable = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.name()? else { continue };
+        if name == default_branch || Some(name) == current.as_deref() {
+            continue;
+        }
+        let Some(tip) = branch.get().target() else { continue };
+
+        let merged = tip == base || repo.graph_descendant_of(base, tip)?;
+        // The upstream name comes from config, so it resolves even when the ref is gone
+        let upstream_gone = repo.branch_upstream_name(&format!("refs/heads/{}", name)).ok()
+            .and_then(|upstream| upstream.as_str().map(|u| repo.find_reference(u).is_err()))
+            .unwrap_or(false);
+        if merged || upstream_gone {
+            prunable.push(PrunableBranch { name: name.to_string(), tip, merged, upstream_gone });
+        }
+    }
+    prunable.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(prunable)
+}
+
+/// Build a GitHub API GET request, authenticated when a token is available
+fn github_get(client: &reqwest::Client, url: &str, token: Option<&str>) -> reqwest::RequestBuilder {
+    let request = client
+        .get(url)
+        .header("User-Agent", "fbcli")
+        .header("Accept", "application/vnd.github+json");
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Confirm a repository exists in the org before cloning it.
+///
+/// Network failures and rate limiting only produce a warning so cloning can
+/// still be attempted; a definite "not found" is reported as an error.
+async fn verify_repository_exists(host: &GitHubHost, repo_name: &str) -> Result<()> {
+    let token = github_token();
+    let client = reqwest::Client::new();
+    let url = format!("{}/repos/{}/{}", host.api_base, host.org, repo_name);
+
+    let response = match github::send(github_get(&client, &url, token.as_deref())).await {
+        Ok(response) => response,
+        Err(e) => {
+            outln!("⚠️  Could not verify repository on GitHub ({}); trying to clone anyway", e);
+            return Ok(());
+        }
+    };
+
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        reqwest::StatusCode::NOT_FOUND if token.is_none() => Err(anyhow!(
+            "Repository '{}' not found in {}. If it is private, set FBCLI_GITHUB_TOKEN (or GITHUB_TOKEN) to a token with access, or pass --no-verify",
+            repo_name, host.org
+        )),
+        reqwest::StatusCode::NOT_FOUND => Err(anyhow!(
+            "Repository '{}' not found in {} (or your token cannot access it)",
+            repo_name, host.org
+        )),
+        reqwest::StatusCode::UNAUTHORIZED => Err(anyhow!(github::TOKEN_REJECTED)),
+        status => {
+            outln!("⚠️  Could not verify repository on GitHub ({}); trying to clone anyway", status);
+            Ok(())
+        }
+    }
+}
+
+async fn update_repositories(args: UpdateArgs) -> Result<()> {
+    let current_dir = current_dir()?;
     
-    // Check if behind/ahead of remote
-    if let Ok(head) = repo.head() {
-        if let Some(branch_name) = head.shorthand() {
-            let remote_branch_name = format!("origin/{}", branch_name);
-            if let Ok(remote_ref) = repo.find_reference(&format!("refs/remotes/{}", remote_branch_name)) {
-                let local_oid = head.target().unwrap();
-                let remote_oid = remote_ref.target().unwrap();
-                
-                if local_oid != remote_oid {
-                    let (ahead, behind) = repo.graph_ahead_behind(local_oid, remote_oid)?;
-                    if ahead > 0 {
-                        println!("  ⬆️  {} commits ahead", ahead);
+    statusln!("🔄 Scanning for Far-Beyond repositories in: {}", style(current_dir.display()).yellow());
+    
+    let repos_found = scan_far_beyond_repos(&current_dir, &args.scan)?;
+    let mut summary = BatchSummary::new("repo update");
+
+    if repos_found.is_empty() {
+        outln!("❌ No Far-Beyond repositories found in current directory");
+        return summary.write_if_requested(args.summary_json.as_deref());
+    }
+
+    outln!("📦 Found {} Far-Beyond repositories:", repos_found.len());
+    
+    for repo_path in &repos_found {
+        let repo_name = repo_path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        outln!("  • {}", style(repo_name).cyan());
+    }
+    
+    if args.dry_run {
+        outln!();
+        outln!("{}", "🔍 Dry run mode - no changes will be made".yellow().bold());
+        for repo_path in &repos_found {
+            summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Skipped).with_message("dry run"));
+        }
+        return summary.write_if_requested(args.summary_json.as_deref());
+    }
+
+    outln!();
+    statusln!("🔄 Updating repositories...");
+
+    // Fetch and update concurrently, then report in scan order
+    let args = Arc::new(args);
+    let credentials = CredentialCache::default();
+    let limit = Arc::new(Semaphore::new(args.jobs as usize));
+    let tasks: Vec<_> = repos_found.iter()
+        .map(|path| {
+            let path = path.clone();
+            let args = args.clone();
+            let credentials = credentials.clone();
+            let limit = limit.clone();
+            tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await?;
+                let job = tokio::task::spawn_blocking(move || match &args.onto {
+                    Some(reference) => UpdateJob::Onto(checkout_onto(&path, &credentials, reference, args.max_retries)),
+                    None => UpdateJob::Update(update_single_repository(&path, &credentials, &args)),
+                });
+                anyhow::Ok(job.await?)
+            })
+        })
+        .collect();
+
+    let mut up_to_date = 0;
+    for (repo_path, task) in repos_found.iter().zip(tasks) {
+        let repo_name = repo_path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let job = match task.await.map_err(anyhow::Error::from).and_then(|job| job) {
+            Ok(job) => job,
+            Err(e) => {
+                outln!("  {} {} {}", style(repo_name).cyan(), "❌ Failed:".red(), e);
+                summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Failed).with_message(e.to_string()));
+                continue;
+            }
+        };
+
+        if args.changed_only && job.is_up_to_date() {
+            up_to_date += 1;
+            summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Unchanged));
+            continue;
+        }
+
+        let reference = args.onto.as_deref().unwrap_or_default();
+        match job {
+            UpdateJob::Onto(result) => {
+                out!("  Moving {} to {}... ", style(repo_name).cyan(), reference);
+                summary.push(match result {
+                    Ok(OntoResult::Moved(oid)) => {
+                        outln!("{} ({})", "✅ Checked out".green(), short_oid(oid));
+                        ItemOutcome::new(repo_path, OutcomeStatus::Success)
+                            .with_message(format!("checked out {} at {}", reference, oid))
+                    }
+                    Ok(OntoResult::AlreadyThere) => {
+                        outln!("{}", "📋 Already there".blue());
+                        ItemOutcome::new(repo_path, OutcomeStatus::Unchanged)
+                    }
+                    Ok(OntoResult::Missing) => {
+                        outln!("{}", format!("⚠️  Skipped: no '{}' in this repository", reference).yellow());
+                        ItemOutcome::new(repo_path, OutcomeStatus::Skipped)
+                            .with_message(format!("'{}' not found", reference))
                     }
-                    if behind > 0 {
-                        println!("  ⬇️  {} commits behind", behind);
+                    Err(e) => {
+                        outln!("{} {}", "❌ Failed:".red(), e);
+                        ItemOutcome::new(repo_path, OutcomeStatus::Failed).with_message(e.to_string())
+                    }
+                });
+            }
+            UpdateJob::Update(result) => {
+                out!("  Updating {}... ", style(repo_name).cyan());
+                match result {
+                    Ok(update) if update.skipped_dirty => {
+                        outln!("{}", "⚠️  Skipped: uncommitted changes (use --autostash or --wip-commit)".yellow());
+                        summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Skipped).with_message("uncommitted changes"));
+                    }
+                    Ok(RepoUpdate { autostash: Some(AutoStash::RolledBack), .. }) => {
+                        outln!("{}", "⚠️  Rolled back: your stashed changes conflict with the update; they were restored on the original commit".yellow());
+                        summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Skipped).with_message("update rolled back, autostash conflicts"));
+                    }
+                    Ok(update) => {
+                        let mut outcome = if update.updated {
+                            outln!("{}", "✅ Updated".green());
+                            ItemOutcome::new(repo_path, OutcomeStatus::Success)
+                        } else {
+                            outln!("{}", "📋 Already up to date".blue());
+                            ItemOutcome::new(repo_path, OutcomeStatus::Unchanged)
+                        };
+                        let mut notes = Vec::new();
+                        if update.fetch_retries > 0 {
+                            outln!("    🔄 Fetch succeeded after {} retries", update.fetch_retries);
+                            notes.push(format!("fetch retries: {}", update.fetch_retries));
+                        }
+                        if update.rebased_commits > 0 {
+                            outln!("    🔄 Rebased {} local commit(s) onto the remote branch", update.rebased_commits);
+                            notes.push(format!("rebased commits: {}", update.rebased_commits));
+                        }
+                        match &update.autostash {
+                            Some(AutoStash::Restored) => outln!("    💾 Uncommitted changes stashed and restored"),
+                            Some(AutoStash::LeftMarkers(reason)) => {
+                                outln!(
+                                    "    {}",
+                                    format!("⚠️  Stashed changes didn't apply cleanly to the updated branch ({}); resolve the conflicts in the working tree, the changes are also kept in stash@{{0}}", reason).yellow()
+                                );
+                                notes.push(format!("autostash conflicts left in the working tree: {}", reason));
+                            }
+                            Some(AutoStash::Kept(reason)) => {
+                                outln!(
+                                    "    {}",
+                                    format!("⚠️  Stashed changes didn't apply cleanly to the updated branch ({}); the working tree was reset and they're kept in stash@{{0}}", reason).yellow()
+                                );
+                                notes.push(format!("autostash kept in stash@{{0}}: {}", reason));
+                            }
+                            Some(AutoStash::RolledBack) | None => {}
+                        }
+                        if let Some(wip) = update.wip_commit {
+                            outln!("    📝 Uncommitted changes saved in WIP commit {}", style(short_oid(wip)).yellow());
+                            notes.push(format!("wip commit: {}", wip));
+                        }
+                        if !update.new_tags.is_empty() {
+                            let tags = update.new_tags.join(", ");
+                            outln!("    🏷️  New tags: {}", style(&tags).magenta());
+                            notes.push(format!("new tags: {}", tags));
+                        }
+                        if !notes.is_empty() {
+                            outcome = outcome.with_message(notes.join("; "));
+                        }
+                        summary.push(outcome);
+                    },
+                    Err(e) => {
+                        outln!("{} {}", "❌ Failed:".red(), e);
+                        summary.push(ItemOutcome::new(repo_path, OutcomeStatus::Failed).with_message(e.to_string()));
                     }
-                } else {
-                    println!("  🔄 Up to date with remote");
                 }
             }
         }
     }
-    
+
+    if args.changed_only {
+        outln!("📋 {} repositories already up to date", up_to_date);
+    }
+
+    outln!();
+    outln!("{}", "✅ Repository update complete!".green().bold());
+    summary.write_if_requested(args.summary_json.as_deref())
+}
+
+/// Default for `repo update --jobs`
+const DEFAULT_UPDATE_JOBS: u32 = 4;
+
+/// Work done for one repository by `repo update`, computed off the async runtime
+enum UpdateJob {
+    /// Moved to the `--onto` ref
+    Onto(Result<OntoResult>),
+    /// Fetched and fast-forwarded
+    Update(Result<RepoUpdate>),
+}
+
+impl UpdateJob {
+    /// Nothing happened worth reporting: no move, no new tags, no WIP commit, no retries
+    fn is_up_to_date(&self) -> bool {
+        match self {
+            UpdateJob::Onto(result) => matches!(result, Ok(OntoResult::AlreadyThere)),
+            UpdateJob::Update(Ok(update)) => {
+                !update.updated
+                    && !update.skipped_dirty
+                    && matches!(update.autostash, None | Some(AutoStash::Restored))
+                    && update.new_tags.is_empty()
+                    && update.wip_commit.is_none()
+                    && update.fetch_retries == 0
+            }
+            UpdateJob::Update(Err(_)) => false,
+        }
+    }
+}
+
+/// What happened to a repository during `repo update`
+#[derive(Debug, Default)]
+struct RepoUpdate {
+    /// Whether the current branch was moved
+    updated: bool,
+    /// Tags that did not exist locally before the fetch (with `--tags`)
+    new_tags: Vec<String>,
+    /// WIP commit holding the previously uncommitted changes (with `--wip-commit`)
+    wip_commit: Option<Oid>,
+    /// Fetch attempts that failed transiently before one succeeded
+    fetch_retries: u32,
+    /// Local commits replayed onto the remote branch (with `--rebase`)
+    rebased_commits: usize,
+    /// Left alone because of uncommitted changes (without `--autostash` or `--wip-commit`)
+    skipped_dirty: bool,
+    /// What happened to uncommitted changes stashed by `--autostash`
+    autostash: Option<AutoStash>,
+}
+
+#[derive(Debug)]
+enum AutoStash {
+    /// Popped back onto the updated branch
+    Restored,
+    /// Conflicted: markers are in the working tree and the stash is kept, with the reason
+    LeftMarkers(String),
+    /// Conflicted: the working tree was reset and the stash is kept, with the reason
+    Kept(String),
+    /// Conflicted: the update was undone and the changes restored
+    RolledBack,
+}
+
+fn update_single_repository(repo_path: &Path, credentials: &CredentialCache, args: &UpdateArgs) -> Result<RepoUpdate> {
+    let mut repo = Repository::open(repo_path)?;
+    let mut result = RepoUpdate::default();
+
+    // Put uncommitted work somewhere safe first so the checkout can't clobber it.
+    // Untracked files alone don't stop an update (the checkout leaves them be),
+    // but --autostash still takes them along.
+    let needs_protecting = has_tracked_changes(&repo)? || (args.autostash && is_dirty(&repo)?);
+    if !needs_protecting {
+        return fetch_and_fast_forward(&repo, credentials, args, result);
+    }
+    if args.wip_commit {
+        result.wip_commit = Some(create_wip_commit(&repo)?);
+        return fetch_and_fast_forward(&repo, credentials, args, result);
+    }
+    if !args.autostash {
+        result.skipped_dirty = true;
+        return Ok(result);
+    }
+
+    let before = repo.head()?.target();
+    stash_changes(&mut repo)?;
+    let update = fetch_and_fast_forward(&repo, credentials, args, result);
+    match (update, restore_stash(&mut repo)) {
+        (Ok(mut update), Ok(())) => {
+            update.autostash = Some(AutoStash::Restored);
+            Ok(update)
+        }
+        (Ok(mut update), Err(e)) => {
+            let outcome = resolve_stash_conflict(&mut repo, args.autostash_conflict, before, e.to_string())?;
+            if matches!(outcome, AutoStash::RolledBack) {
+                update.updated = false;
+            }
+            update.autostash = Some(outcome);
+            Ok(update)
+        }
+        (Err(e), Ok(())) => Err(e),
+        (Err(e), Err(_)) => Err(anyhow!("{} (uncommitted changes kept in stash@{{0}})", e)),
+    }
+}
+
+const AUTOSTASH_MESSAGE: &str = "fbcli autostash before repo update";
+
+/// Apply `--autostash-conflict` after the autostash failed to apply to the updated branch
+fn resolve_stash_conflict(
+    repo: &mut Repository,
+    strategy: AutostashConflict,
+    before: Option<Oid>,
+    reason: String,
+) -> Result<AutoStash> {
+    match strategy {
+        AutostashConflict::LeaveMarkers => Ok(AutoStash::LeftMarkers(reason)),
+        AutostashConflict::Keep => {
+            discard_working_tree(repo)?;
+            Ok(AutoStash::Kept(reason))
+        }
+        AutostashConflict::Abort => {
+            let before = before.ok_or_else(|| anyhow!("{}; the branch had no commit to roll back to", reason))?;
+            let head = repo.head()?;
+            let branch = head.name().ok_or_else(|| anyhow!("HEAD is not a valid branch name"))?.to_string();
+            drop(head);
+            repo.reference(&branch, before, true, "fbcli: roll back update after autostash conflict")
+                .map_err(|e| anyhow!("could not roll back {} ({}); your changes are kept in stash@{{0}}", branch, e.message()))?;
+            discard_working_tree(repo)?;
+            restore_stash(repo).map_err(|_| {
+                anyhow!("rolled back the update, but the stash didn't apply to the original commit either; it's kept in stash@{{0}}")
+            })?;
+            Ok(AutoStash::RolledBack)
+        }
+    }
+}
+
+/// Reset the index and working tree to HEAD. Untracked files are removed too:
+/// the autostash took all of them, so any left were put back by the failed apply.
+/// Only called while the autostash is still in place, which errors point to.
+fn discard_working_tree(repo: &Repository) -> Result<()> {
+    let reset = || -> std::result::Result<(), git2::Error> {
+        let head = repo.head()?.peel_to_commit()?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.force().remove_untracked(true);
+        repo.reset(head.as_object(), git2::ResetType::Hard, Some(&mut checkout))
+    };
+    reset().map_err(|e| anyhow!("could not reset the working tree ({}); your changes are kept in stash@{{0}}", e.message()))
+}
+
+/// Stash every change in the working tree, including untracked files
+fn stash_changes(repo: &mut Repository) -> Result<Oid> {
+    let signature = repo.signature()
+        .or_else(|_| Signature::now("fbcli", "fbcli@localhost"))?;
+    Ok(repo.stash_save(&signature, AUTOSTASH_MESSAGE, Some(git2::StashFlags::INCLUDE_UNTRACKED))?)
+}
+
+/// Pop the autostash. Like `git stash pop`, a stash that doesn't apply
+/// cleanly stays in place as `stash@{0}`, with any conflicts left in the working tree.
+fn restore_stash(repo: &mut Repository) -> Result<()> {
+    repo.stash_apply(0, None)
+        .map_err(|e| anyhow!("{}", e.message()))?;
+    if repo.index()?.has_conflicts() {
+        return Err(anyhow!("they conflict with the update"));
+    }
+    repo.stash_drop(0)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+fn fetch_and_fast_forward(
+    repo: &Repository,
+    credentials: &CredentialCache,
+    args: &UpdateArgs,
+    mut result: RepoUpdate,
+) -> Result<RepoUpdate> {
+    let tags_before = if args.tags { local_tag_names(repo)? } else { Vec::new() };
+
+    // Remember where origin's branch was, to recognize a force-push after fetching
+    let tracked_before = repo.head().ok()
+        .and_then(|head| head.shorthand().map(|name| format!("refs/remotes/origin/{}", name)))
+        .and_then(|name| repo.refname_to_id(&name).ok());
+
+    // Fetch from origin
+    let mut remote = repo.find_remote("origin")?;
+    let refspecs: &[&str] = &[];
+    result.fetch_retries = credentials.fetch_with_retries(repo, &mut remote, refspecs, args.tags, args.max_retries)?;
+
+    if args.tags {
+        result.new_tags = local_tag_names(repo)?
+            .into_iter()
+            .filter(|tag| !tags_before.contains(tag))
+            .collect();
+    }
+    
+    // Get current branch
+    let head = repo.head()?;
+    if repo.head_detached()? {
+        return Err(anyhow!("HEAD is detached (e.g. after --onto); check out a branch to update it"));
+    }
+    let local_oid = head.target().unwrap();
+    
+    // Get remote branch
+    let branch_name = head.shorthand().unwrap_or("main");
+    let remote_branch_name = format!("origin/{}", branch_name);
+    let remote_ref = repo.find_reference(&format!("refs/remotes/{}", remote_branch_name))?;
+    let remote_oid = remote_ref.target().unwrap();
+    
+    // A WIP commit sits on top of the commit the branch was really at
+    let base_oid = match result.wip_commit {
+        Some(_) => repo.find_commit(local_oid)?.parent_id(0)?,
+        None => local_oid,
+    };
+
+    // Check if update is needed
+    if local_oid == remote_oid || base_oid == remote_oid {
+        return Ok(result); // Already up to date
+    }
+
+    if result.wip_commit.is_some() {
+        if !repo.graph_descendant_of(remote_oid, base_oid)? {
+            if args.rebase {
+                return rebase_local_commits(repo, branch_name, remote_oid, tracked_before, result);
+            }
+            let reason = non_fast_forward_error(repo, branch_name, base_oid, remote_oid, tracked_before)?;
+            return Err(anyhow!("{} (WIP commit kept)", reason));
+        }
+
+        // Fast-forward to the remote and replay the WIP commit on top of it
+        let new_oid = replay_commit(repo, local_oid, remote_oid)?;
+        let mut reference = repo.find_reference(&format!("refs/heads/{}", branch_name))?;
+        reference.set_target(new_oid, "Fast-forward with WIP commit")?;
+        repo.set_head(&format!("refs/heads/{}", branch_name))?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+
+        result.updated = true;
+        result.wip_commit = Some(new_oid);
+        return Ok(result);
+    }
+    
+    // Perform fast-forward merge
+    let (analysis, _) = repo.merge_analysis(&[&repo.find_annotated_commit(remote_oid)?])?;
+    
+    if analysis.is_fast_forward() {
+        // Update the reference
+        let mut reference = repo.find_reference(&format!("refs/heads/{}", branch_name))?;
+        reference.set_target(remote_oid, "Fast-forward")?;
+        
+        // Update working directory
+        repo.set_head(&format!("refs/heads/{}", branch_name))?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+        
+        result.updated = true;
+        Ok(result)
+    } else if args.rebase {
+        rebase_local_commits(repo, branch_name, remote_oid, tracked_before, result)
+    } else {
+        Err(non_fast_forward_error(repo, branch_name, local_oid, remote_oid, tracked_before)?)
+    }
+}
+
+/// `repo update --rebase`: replay the branch's local commits on top of
+/// `origin/<branch>` (at `remote_oid`). If upstream was force-pushed, only the
+/// commits made since its old tip are replayed, like `git pull --rebase`.
+/// A conflicting rebase is aborted, leaving the branch where it was.
+fn rebase_local_commits(
+    repo: &Repository,
+    branch: &str,
+    remote_oid: Oid,
+    tracked_before: Option<Oid>,
+    mut result: RepoUpdate,
+) -> Result<RepoUpdate> {
+    let local_oid = repo.head()?.target().ok_or_else(|| anyhow!("HEAD has no target"))?;
+    if repo.graph_descendant_of(local_oid, remote_oid)? {
+        // Only local commits on top of origin; nothing to rebase onto
+        return Ok(result);
+    }
+    // Commits reachable from the old upstream tip were rewritten upstream, not made locally
+    let fork_point = match tracked_before {
+        Some(old) if old != remote_oid
+            && !repo.graph_descendant_of(remote_oid, old)?
+            && (old == local_oid || repo.graph_descendant_of(local_oid, old)?) => old,
+        _ => repo.merge_base(local_oid, remote_oid)?,
+    };
+
+    let head = repo.reference_to_annotated_commit(&repo.head()?)?;
+    let upstream = repo.find_annotated_commit(fork_point)?;
+    let onto = repo.find_annotated_commit(remote_oid)?;
+    let committer = repo.signature()
+        .or_else(|_| Signature::now("fbcli", "fbcli@localhost"))?;
+
+    let mut rebase = repo.rebase(Some(&head), Some(&upstream), Some(&onto), None)?;
+    let mut replayed = 0;
+    while let Some(operation) = rebase.next() {
+        let step = operation.map_err(anyhow::Error::from).and_then(|operation| {
+            if repo.index()?.has_conflicts() {
+                return Err(anyhow!(
+                    "rebasing {} onto origin/{} hit conflicts; the rebase was aborted, so rebase it manually",
+                    short_oid(operation.id()),
+                    branch
+                ));
+            }
+            match rebase.commit(None, &committer, None) {
+                Ok(_) => Ok(true),
+                // The change is already upstream; git drops such commits too
+                Err(e) if e.code() == git2::ErrorCode::Applied => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        });
+        match step {
+            Ok(committed) => replayed += usize::from(committed),
+            Err(e) => {
+                rebase.abort()?;
+                return Err(e);
+            }
+        }
+    }
+    rebase.finish(Some(&committer))?;
+
+    result.updated = true;
+    result.rebased_commits = replayed;
+    if result.wip_commit.is_some() {
+        // The WIP commit is the last one replayed, so it's the new tip
+        result.wip_commit = repo.head()?.target();
+    }
+    Ok(result)
+}
+
+/// Explain why `branch` can't be fast-forwarded to `origin/<branch>`, telling a
+/// force-pushed upstream apart from local commits that simply need merging.
+/// `tracked_before` is where `origin/<branch>` pointed before the fetch.
+fn non_fast_forward_error(
+    repo: &Repository,
+    branch: &str,
+    local_oid: Oid,
+    remote_oid: Oid,
+    tracked_before: Option<Oid>,
+) -> Result<anyhow::Error> {
+    // Upstream was rewritten if its old tip is no longer in its history
+    let old_tip = match tracked_before {
+        Some(old) if old != remote_oid && !repo.graph_descendant_of(remote_oid, old)? => old,
+        _ => return Ok(anyhow!("Cannot fast-forward, manual merge required")),
+    };
+
+    let old = short_oid(old_tip);
+    if local_oid == old_tip || repo.graph_descendant_of(old_tip, local_oid)? {
+        // No local commits on top of the old upstream, so nothing is lost by resetting
+        Ok(anyhow!(
+            "origin/{branch} was force-pushed ({old} is no longer in its history); \
+             run `git reset --hard origin/{branch}` to follow it"
+        ))
+    } else {
+        Ok(anyhow!(
+            "origin/{branch} was force-pushed ({old} is no longer in its history); \
+             move your commits with `git rebase --onto origin/{branch} {old} {branch}` \
+             or discard them with `git reset --hard origin/{branch}`"
+        ))
+    }
+}
+
+/// Result of moving a repository to the `--onto` ref
+enum OntoResult {
+    Moved(Oid),
+    AlreadyThere,
+    Missing,
+}
+
+/// Fetch (with tags) and check out `reference` as a detached HEAD
+fn checkout_onto(repo_path: &Path, credentials: &CredentialCache, reference: &str, max_retries: u32) -> Result<OntoResult> {
+    let repo = Repository::open(repo_path)?;
+
+    let mut remote = repo.find_remote("origin")?;
+    let refspecs: &[&str] = &[];
+    credentials.fetch_with_retries(&repo, &mut remote, refspecs, true, max_retries)?;
+
+    // Tags first since they're the usual release pins, then remote branches, then anything revparse accepts
+    let candidates = [
+        format!("refs/tags/{}", reference),
+        format!("refs/remotes/origin/{}", reference),
+        reference.to_string(),
+    ];
+    let Some(commit) = candidates.iter()
+        .find_map(|spec| repo.revparse_single(spec).ok())
+        .and_then(|object| object.peel_to_commit().ok())
+    else {
+        return Ok(OntoResult::Missing);
+    };
+
+    let already_there = repo.head().ok()
+        .and_then(|head| head.target())
+        .is_some_and(|oid| oid == commit.id());
+    if already_there {
+        if !repo.head_detached()? {
+            repo.set_head_detached(commit.id())?;
+        }
+        return Ok(OntoResult::AlreadyThere);
+    }
+
+    if is_dirty(&repo)? {
+        return Err(anyhow!("uncommitted changes; commit or stash them before moving to {}", reference));
+    }
+
+    repo.checkout_tree(commit.as_object(), Some(git2::build::CheckoutBuilder::default().safe()))?;
+    repo.set_head_detached(commit.id())?;
+    Ok(OntoResult::Moved(commit.id()))
+}
+
+/// Whether the working tree or index has changes (ignored files excluded)
+fn is_dirty(repo: &Repository) -> Result<bool> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    Ok(!repo.statuses(Some(&mut options))?.is_empty())
+}
+
+/// Whether tracked files are changed in the index or working tree (or conflicted)
+fn has_tracked_changes(repo: &Repository) -> Result<bool> {
+    let tracked = git2::Status::INDEX_NEW
+        | git2::Status::INDEX_MODIFIED
+        | git2::Status::INDEX_DELETED
+        | git2::Status::INDEX_RENAMED
+        | git2::Status::INDEX_TYPECHANGE
+        | git2::Status::WT_MODIFIED
+        | git2::Status::WT_DELETED
+        | git2::Status::WT_RENAMED
+        | git2::Status::WT_TYPECHANGE
+        | git2::Status::CONFLICTED;
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    Ok(repo.statuses(Some(&mut options))?.iter().any(|entry| entry.status().intersects(tracked)))
+}
+
+const WIP_COMMIT_MESSAGE: &str = "WIP: fbcli auto-commit before repo update";
+
+/// Commit every change in the working tree (including untracked files) on HEAD
+fn create_wip_commit(repo: &Repository) -> Result<Oid> {
+    let mut index = repo.index()?;
+    index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
+    index.update_all(["*"].iter(), None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let parent = repo.head()?.peel_to_commit()?;
+    let signature = repo.signature()
+        .or_else(|_| Signature::now("fbcli", "fbcli@localhost"))?;
+    let oid = repo.commit(Some("HEAD"), &signature, &signature, WIP_COMMIT_MESSAGE, &tree, &[&parent])?;
+    Ok(oid)
+}
+
+/// Re-create `commit` on top of `onto`, returning the new commit id
+fn replay_commit(repo: &Repository, commit: Oid, onto: Oid) -> Result<Oid> {
+    let commit = repo.find_commit(commit)?;
+    let onto = repo.find_commit(onto)?;
+
+    let mut index = repo.cherrypick_commit(&commit, &onto, 0, None)?;
+    if index.has_conflicts() {
+        return Err(anyhow!(
+            "WIP commit {} conflicts with the remote changes; manual merge required",
+            short_oid(commit.id())
+        ));
+    }
+    let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+
+    let oid = repo.commit(
+        None,
+        &commit.author(),
+        &commit.committer(),
+        commit.message().unwrap_or(WIP_COMMIT_MESSAGE),
+        &tree,
+        &[&onto],
+    )?;
+    Ok(oid)
+}
+
+fn short_oid(oid: Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+fn local_tag_names(repo: &Repository) -> Result<Vec<String>> {
+    Ok(repo.tag_names(None)?
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect())
+}
+
+async fn check_repository_status(args: &StatusArgs) -> Result<()> {
+    let current_dir = current_dir()?;
+    
+    statusln!("📊 Checking status of Far-Beyond repositories...");
+    statusln!("📂 Scanning directory: {}", style(current_dir.display()).yellow());
+    outln!();
+
+    let mut summary = BatchSummary::new("repo status");
+    let mut reports = Vec::new();
+    let client = reqwest::Client::new();
+    let host = GitHubHost::resolve(&GitHubHostArgs::default())?;
+    let token = github_token();
+    let mut branch_cache = if args.verify_remote { DefaultBranchCache::load() } else { DefaultBranchCache::default() };
+
+    // Compute local status concurrently, then render in scan order
+    let mut paths = scan_far_beyond_repos(&current_dir, &args.scan)?;
+    let owners: Vec<String> = if args.group_by_owner {
+        // Stable sort keeps scan order within each owner
+        paths.sort_by_cached_key(|path| origin_owner(path));
+        paths.iter().map(|path| origin_owner(path)).collect()
+    } else {
+        Vec::new()
+    };
+    let broken = if args.health { find_unopenable_repos(&current_dir, args.scan.recursive)? } else { Vec::new() };
+    let repos_found = paths.len() + broken.len();
+    let limit = Arc::new(Semaphore::new(status_concurrency()));
+    let health = args.health;
+    let fetch = args.fetch.then(CredentialCache::default);
+    let tasks: Vec<_> = paths.iter()
+        .map(|path| {
+            let path = path.clone();
+            let limit = limit.clone();
+            let fetch = fetch.clone();
+            tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await?;
+                tokio::task::spawn_blocking(move || collect_repository_status(&path, health, fetch.as_ref())).await?
+            })
+        })
+        .collect();
+
+    for (index, (path, task)) in paths.iter().zip(tasks).enumerate() {
+        if let Some(owner) = owners.get(index) {
+            if index == 0 || owners[index - 1] != *owner {
+                let count = owners.iter().filter(|o| *o == owner).count();
+                outln!("{} {} ({} repositories)", "🏷️".bold(), style(owner).magenta().bold(), count);
+                outln!();
+            }
+        }
+        let status = task.await.map_err(anyhow::Error::from).and_then(|status| status);
+        let report = StatusReport::new(path, status);
+        show_repository_status(&report);
+        let mut outcome = match &report.error {
+            None => ItemOutcome::new(path, OutcomeStatus::Success),
+            Some(e) => ItemOutcome::new(path, OutcomeStatus::Failed).with_message(e.clone()),
+        };
+        reports.push(report);
+        if args.verify_remote {
+            let default_branch =
+                fetch_default_branch(&client, &host, path, token.as_deref(), &mut branch_cache, args.refresh).await;
+            if let Ok(repo) = Repository::open(path) {
+                if let Some(mismatch) = show_default_branch_check(&repo, default_branch) {
+                    outcome = outcome.with_message(mismatch);
+                }
+            }
+        }
+        summary.push(outcome);
+        outln!();
+    }
+
+    // Their origin can't be read, so these may not all be Far-Beyond repositories
+    for (path, error) in &broken {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+        outln!("{} {}", "📦".bold(), style(name).cyan().bold());
+        outln!("  {} {}", "❌ Cannot open repository:".red(), error);
+        outln!();
+        summary.push(ItemOutcome::new(path, OutcomeStatus::Failed).with_message(error.to_string()));
+        reports.push(StatusReport::new(path, Err(anyhow!("Cannot open repository: {}", error))));
+    }
+
+    if args.json {
+        // Raw stdout: everything else is suppressed in JSON mode
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    }
+
+    if repos_found == 0 {
+        outln!("❌ No Far-Beyond repositories found in current directory");
+    } else {
+        outln!("📈 Status check complete for {} repositories", repos_found);
+    }
+    if !args.fetch && !paths.is_empty() {
+        outln!("ℹ Ahead/behind counts are as of each repository's last fetch; pass --fetch to refresh them");
+    }
+    if args.health && summary.counts.failed > 0 {
+        outln!("⚠️  {} repositories need attention (repair or re-clone them)", summary.counts.failed);
+    }
+    if args.verify_remote {
+        branch_cache.save();
+    }
+
+    summary.write_if_requested(args.summary_json.as_deref())
+}
+
+/// Look up a repository's default branch on GitHub from its origin remote,
+/// answering from the cache while its entry is fresh (unless `refresh`)
+async fn fetch_default_branch(
+    client: &reqwest::Client,
+    host: &GitHubHost,
+    repo_path: &Path,
+    token: Option<&str>,
+    cache: &mut DefaultBranchCache,
+    refresh: bool,
+) -> Result<String> {
+    let url = Repository::open(repo_path)?
+        .find_remote("origin")?
+        .url()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("origin remote has no URL"))?;
+    let remote = parse_github_remote(&url)
+        .ok_or_else(|| anyhow!("origin is not a GitHub remote: {}", url))?;
+
+    let key = default_branch_key(&remote.host, &remote.owner, &remote.repo);
+    if !refresh {
+        if let Some(branch) = cache.get(&key) {
+            return Ok(branch.to_string());
+        }
+    }
+    let branch = fetch_repo_info(client, host, &remote, token).await?.default_branch;
+    cache.insert(&key, &branch);
+    Ok(branch)
+}
+
+/// How long a cached default branch is trusted before GitHub is asked again
+const DEFAULT_BRANCH_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Repositories' default branches as last seen on GitHub, kept in the user
+/// cache dir so repeated `repo status --verify-remote` runs skip the API
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DefaultBranchCache {
+    /// Keyed by `host/owner/repo`, lowercased
+    entries: BTreeMap<String, CachedDefaultBranch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedDefaultBranch {
+    branch: String,
+    /// Unix timestamp of the lookup
+    fetched_at: i64,
+}
+
+impl DefaultBranchCache {
+    fn path() -> Result<PathBuf> {
+        Ok(cache_dir()?.join("default-branches.json"))
+    }
+
+    /// Load the cache; a missing or unreadable file is just an empty cache
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache back. Failing to do so only costs API calls next time, so errors are ignored.
+    fn save(&self) {
+        let Ok(path) = Self::path() else { return };
+        if let (Some(dir), Ok(contents)) = (path.parent(), serde_json::to_string_pretty(self)) {
+            let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, contents));
+        }
+    }
+
+    /// The cached branch, if it was looked up recently enough
+    fn get(&self, key: &str) -> Option<&str> {
+        let entry = self.entries.get(key)?;
+        let age = Utc::now().timestamp().saturating_sub(entry.fetched_at);
+        (age >= 0 && (age as u64) < DEFAULT_BRANCH_CACHE_MAX_AGE.as_secs()).then_some(entry.branch.as_str())
+    }
+
+    fn insert(&mut self, key: &str, branch: &str) {
+        let entry = CachedDefaultBranch { branch: branch.to_string(), fetched_at: Utc::now().timestamp() };
+        self.entries.insert(key.to_string(), entry);
+    }
+}
+
+/// Cache key for a repository, the same whichever URL form its remote uses
+fn default_branch_key(host: &str, owner: &str, repo: &str) -> String {
+    format!("{}/{}/{}", host, owner, repo).to_lowercase()
+}
+
+/// Fetch repository metadata from the GitHub API. Renamed or transferred
+/// repositories redirect, so the result carries the current name.
+async fn fetch_repo_info(
+    client: &reqwest::Client,
+    host: &GitHubHost,
+    remote: &GitHubRemote,
+    token: Option<&str>,
+) -> Result<GitHubRepo> {
+    let api_url = format!("{}/repos/{}/{}", host.api_base_for(&remote.host), remote.owner, remote.repo);
+    let response = github::send(github_get(client, &api_url, token)).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("GitHub API request failed: {}", response.status()));
+    }
+    response.json().await
+        .context("Failed to parse GitHub API response")
+}
+
+/// Report whether the local branches match the remote default branch.
+/// Returns a description of the mismatch, if any.
+fn show_default_branch_check(repo: &Repository, default_branch: Result<String>) -> Option<String> {
+    let default_branch = match default_branch {
+        Ok(branch) => branch,
+        Err(e) => {
+            outln!("  ⚠️  Could not verify default branch: {}", e);
+            return None;
+        }
+    };
+
+    let current = repo.head().ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+    if current.as_deref() == Some(default_branch.as_str()) {
+        outln!("  ✅ On the remote default branch '{}'", default_branch);
+        return None;
+    }
+
+    let current = current.unwrap_or_else(|| "detached HEAD".to_string());
+    let message = format!("remote default branch is '{}' but the checkout is on '{}'", default_branch, current);
+    outln!("  ⚠️  Remote default branch is '{}' but the checkout is on '{}'",
+        style(&default_branch).yellow(), current);
+    if repo.find_branch(&default_branch, BranchType::Local).is_ok() {
+        outln!("     Switch with: git checkout {}", default_branch);
+    }
+    Some(message)
+}
+
+fn status_concurrency() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+}
+
+/// Local state of a repository, gathered off the async runtime
+#[derive(Debug, Default, Serialize)]
+struct RepoStatus {
+    branch: Option<String>,
+    clean: bool,
+    /// Changed in the working tree but not staged (modified, deleted, renamed)
+    modified: Vec<String>,
+    untracked: Vec<String>,
+    /// Changes in the index
+    staged: Vec<String>,
+    /// Commits ahead of `origin/<branch>`, when that ref exists
+    ahead: Option<usize>,
+    /// Commits behind `origin/<branch>`, when that ref exists
+    behind: Option<usize>,
+    /// Why `--fetch` failed, leaving the counts as of the previous fetch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fetch_error: Option<String>,
+}
+
+/// One repository in `repo status`, rendered as text or as an element of `--json`
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    name: String,
+    path: PathBuf,
+    #[serde(flatten)]
+    status: Option<RepoStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl StatusReport {
+    fn new(path: &Path, status: Result<RepoStatus>) -> Self {
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let (status, error) = match status {
+            Ok(status) => (Some(status), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        Self { name, path: path.to_path_buf(), status, error }
+    }
+}
+
+/// Compute a repository's status. Opens its own handle so it can run on any thread.
+/// In `strict` mode an unreadable HEAD is an error rather than "no branch".
+/// With `fetch`, origin is fetched first; a failed fetch is recorded, not fatal.
+fn collect_repository_status(repo_path: &Path, strict: bool, fetch: Option<&CredentialCache>) -> Result<RepoStatus> {
+    let repo = Repository::open(repo_path)?;
+    if strict {
+        check_head_readable(&repo)?;
+    }
+    let branch = repo.head().ok().and_then(|head| head.shorthand().map(str::to_string));
+
+    let mut status = RepoStatus { branch, ..Default::default() };
+
+    if let Some(credentials) = fetch {
+        let fetched = repo.find_remote("origin")
+            .map_err(anyhow::Error::from)
+            .and_then(|mut remote| credentials.fetch_with_retries(&repo, &mut remote, &[], false, STATUS_FETCH_RETRIES));
+        if let Err(e) = fetched {
+            status.fetch_error = Some(e.to_string());
+        }
+    }
+
+    // Check for uncommitted changes
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let worktree = git2::Status::WT_MODIFIED | git2::Status::WT_DELETED
+        | git2::Status::WT_RENAMED | git2::Status::WT_TYPECHANGE;
+    let index = git2::Status::INDEX_NEW | git2::Status::INDEX_MODIFIED | git2::Status::INDEX_DELETED
+        | git2::Status::INDEX_RENAMED | git2::Status::INDEX_TYPECHANGE;
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let flags = entry.status();
+        let path = entry.path().unwrap_or("?").to_string();
+        if flags.intersects(index) {
+            status.staged.push(path.clone());
+        }
+        if flags.intersects(worktree) {
+            status.modified.push(path.clone());
+        }
+        if flags.contains(git2::Status::WT_NEW) {
+            status.untracked.push(path);
+        }
+    }
+    status.clean = status.modified.is_empty() && status.untracked.is_empty() && status.staged.is_empty();
+
+    // Check if behind/ahead of remote
+    if let (Ok(head), Some(branch_name)) = (repo.head(), &status.branch) {
+        let remote_ref_name = format!("refs/remotes/origin/{}", branch_name);
+        if let Ok(remote_ref) = repo.find_reference(&remote_ref_name) {
+            if let (Some(local_oid), Some(remote_oid)) = (head.target(), remote_ref.target()) {
+                let (ahead, behind) = repo.graph_ahead_behind(local_oid, remote_oid)?;
+                status.ahead = Some(ahead);
+                status.behind = Some(behind);
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Retries for a `repo status --fetch` fetch that fails with a network error
+const STATUS_FETCH_RETRIES: u32 = 1;
+
+/// Resolve HEAD down to its commit; an unborn branch is fine, anything else is corruption
+fn check_head_readable(repo: &Repository) -> Result<()> {
+    match repo.head() {
+        Ok(head) => {
+            head.peel_to_commit().context("HEAD does not point at a readable commit")?;
+            Ok(())
+        }
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(()),
+        Err(e) => Err(anyhow::Error::from(e).context("Cannot read HEAD")),
+    }
+}
+
+/// Checkouts with a `.git` entry that git2 fails to open, found the same way
+/// `scan_far_beyond_repos` finds checkouts (which skips them silently)
+fn find_unopenable_repos(dir: &Path, recursive: bool) -> Result<Vec<(PathBuf, git2::Error)>> {
+    let mut broken = Vec::new();
+    for path in scan_candidates(dir, recursive)? {
+        if let Err(e) = Repository::open(&path) {
+            broken.push((path, e));
+        }
+    }
+    broken.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(broken)
+}
+
+fn show_repository_status(report: &StatusReport) {
+    outln!("{} {}", "📦".bold(), style(&report.name).cyan().bold());
+    let Some(status) = &report.status else {
+        if let Some(e) = &report.error {
+            outln!("  {} {}", "❌ Failed to read status:".red(), e);
+        }
+        return;
+    };
+
+    if let Some(branch_name) = &status.branch {
+        outln!("  🌿 Branch: {}", style(branch_name).green());
+    }
+    if let Some(e) = &status.fetch_error {
+        outln!("  {}", format!("⚠️  Fetch failed ({}); ahead/behind may be stale", e).yellow());
+    }
+
+    if status.clean {
+        outln!("  ✅ Working directory clean");
+    } else {
+        let changes: Vec<String> = [("modified", &status.modified), ("untracked", &status.untracked), ("staged", &status.staged)]
+            .iter()
+            .filter(|(_, files)| !files.is_empty())
+            .map(|(kind, files)| format!("{} {}", files.len(), kind))
+            .collect();
+        outln!("  ⚠️  Uncommitted changes: {}", changes.join(", "));
+    }
+
+    match (status.ahead, status.behind) {
+        (Some(0), Some(0)) => outln!("  🔄 Up to date with remote"),
+        (Some(ahead), Some(behind)) => {
+            if ahead > 0 {
+                outln!("  ⬆️  {} commits ahead", ahead);
+            }
+            if behind > 0 {
+                outln!("  ⬇️  {} commits behind", behind);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `contents` to `file` and commit it on HEAD
+    fn commit_file(repo: &Repository, file: &str, contents: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::write(workdir.join(file), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parents: Vec<_> = repo.head().ok().and_then(|head| head.peel_to_commit().ok()).into_iter().collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, file, &tree, &parents).unwrap()
+    }
+
+    /// A repository with one commit on `master`
+    fn test_repo() -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/master").unwrap();
+        commit_file(&repo, "README.md", "hello\n");
+        (dir, repo)
+    }
+
+    fn update_args(args: &[&str]) -> UpdateArgs {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            args: UpdateArgs,
+        }
+        <Cli as clap::Parser>::try_parse_from(std::iter::once("update").chain(args.iter().copied()))
+            .unwrap()
+            .args
+    }
+
+    /// An upstream repository with a clone of it, after which upstream changes
+    /// `README.md` so the clone has something to fast-forward to
+    fn cloned_repo() -> (tempfile::TempDir, Repository, tempfile::TempDir) {
+        let (upstream_dir, upstream) = test_repo();
+        let clone_dir = tempfile::tempdir().unwrap();
+        CredentialCache::default()
+            .clone_repo(upstream_dir.path().to_str().unwrap(), clone_dir.path(), None)
+            .unwrap();
+        commit_file(&upstream, "README.md", "hello from upstream\n");
+        (upstream_dir, upstream, clone_dir)
+    }
+
+    fn stash_count(repo: &mut Repository) -> usize {
+        let mut count = 0;
+        repo.stash_foreach(|_, _, _| {
+            count += 1;
+            true
+        }).unwrap();
+        count
+    }
+
+    fn head_oid(repo: &Repository) -> Oid {
+        repo.head().unwrap().target().unwrap()
+    }
+
+    #[test]
+    fn test_update_skips_tracked_changes_only() {
+        let (_upstream_dir, upstream, clone_dir) = cloned_repo();
+        let path = clone_dir.path();
+        let credentials = CredentialCache::default();
+
+        // A stray untracked file doesn't stop the update and is left alone
+        fs::write(path.join("notes.txt"), "mine\n").unwrap();
+        let update = update_single_repository(path, &credentials, &update_args(&[])).unwrap();
+        assert!(update.updated && !update.skipped_dirty);
+        assert_eq!(head_oid(&Repository::open(path).unwrap()), head_oid(&upstream));
+        assert_eq!(fs::read_to_string(path.join("notes.txt")).unwrap(), "mine\n");
+
+        // A change to a tracked file does
+        commit_file(&upstream, "CHANGELOG.md", "v2\n");
+        fs::write(path.join("README.md"), "local edit\n").unwrap();
+        let update = update_single_repository(path, &credentials, &update_args(&[])).unwrap();
+        assert!(update.skipped_dirty && !update.updated);
+        assert_ne!(head_oid(&Repository::open(path).unwrap()), head_oid(&upstream));
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "local edit\n");
+    }
+
+    #[test]
+    fn test_update_autostash_restores_changes() {
+        let (_upstream_dir, upstream, clone_dir) = cloned_repo();
+        let path = clone_dir.path();
+        // A staged new file and an untracked one, neither touched by the update
+        fs::write(path.join("local.txt"), "staged\n").unwrap();
+        let repo = Repository::open(path).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("local.txt")).unwrap();
+        index.write().unwrap();
+        fs::write(path.join("notes.txt"), "untracked\n").unwrap();
+
+        let args = update_args(&["--autostash"]);
+        let update = update_single_repository(path, &CredentialCache::default(), &args).unwrap();
+        assert!(update.updated);
+        assert!(matches!(update.autostash, Some(AutoStash::Restored)));
+
+        let mut repo = Repository::open(path).unwrap();
+        assert_eq!(head_oid(&repo), head_oid(&upstream));
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "hello from upstream\n");
+        assert_eq!(fs::read_to_string(path.join("local.txt")).unwrap(), "staged\n");
+        assert_eq!(fs::read_to_string(path.join("notes.txt")).unwrap(), "untracked\n");
+        assert_eq!(stash_count(&mut repo), 0);
+    }
+
+    #[test]
+    fn test_update_autostash_conflict_keep() {
+        let (_upstream_dir, upstream, clone_dir) = cloned_repo();
+        let path = clone_dir.path();
+        fs::write(path.join("README.md"), "local edit\n").unwrap();
+
+        let args = update_args(&["--autostash", "--autostash-conflict", "keep"]);
+        let update = update_single_repository(path, &CredentialCache::default(), &args).unwrap();
+        assert!(update.updated);
+        assert!(matches!(update.autostash, Some(AutoStash::Kept(_))));
+
+        // The branch is updated, the working tree is clean and the changes wait in the stash
+        let mut repo = Repository::open(path).unwrap();
+        assert_eq!(head_oid(&repo), head_oid(&upstream));
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "hello from upstream\n");
+        assert!(!is_dirty(&repo).unwrap());
+        assert_eq!(stash_count(&mut repo), 1);
+    }
+
+    #[test]
+    fn test_update_autostash_conflict_abort() {
+        let (_upstream_dir, upstream, clone_dir) = cloned_repo();
+        let path = clone_dir.path();
+        let before = head_oid(&Repository::open(path).unwrap());
+        fs::write(path.join("README.md"), "local edit\n").unwrap();
+
+        let args = update_args(&["--autostash", "--autostash-conflict", "abort"]);
+        let update = update_single_repository(path, &CredentialCache::default(), &args).unwrap();
+        assert!(!update.updated);
+        assert!(matches!(update.autostash, Some(AutoStash::RolledBack)));
+
+        // The branch is back where it was and the changes are restored from the stash
+        let mut repo = Repository::open(path).unwrap();
+        assert_eq!(head_oid(&repo), before);
+        assert_ne!(head_oid(&repo), head_oid(&upstream));
+        assert_eq!(fs::read_to_string(path.join("README.md")).unwrap(), "local edit\n");
+        assert_eq!(stash_count(&mut repo), 0);
+    }
+
+    #[test]
+    fn test_update_autostash_conflict_leave_markers() {
+        let (_upstream_dir, upstream, clone_dir) = cloned_repo();
+        let path = clone_dir.path();
+        fs::write(path.join("README.md"), "local edit\n").unwrap();
+
+        let args = update_args(&["--autostash"]);
+        let update = update_single_repository(path, &CredentialCache::default(), &args).unwrap();
+        assert!(update.updated);
+        assert!(matches!(update.autostash, Some(AutoStash::LeftMarkers(_))));
+
+        // The branch is updated, the conflict is in the working tree and the stash is kept
+        let mut repo = Repository::open(path).unwrap();
+        assert_eq!(head_oid(&repo), head_oid(&upstream));
+        let readme = fs::read_to_string(path.join("README.md")).unwrap();
+        assert!(readme.contains("<<<<<<<") && readme.contains("local edit") && readme.contains("hello from upstream"));
+        assert!(repo.index().unwrap().has_conflicts());
+        assert_eq!(stash_count(&mut repo), 1);
+    }
+
+    #[test]
+    fn test_default_branch_check_uses_checked_out_branch() {
+        let (_dir, repo) = test_repo();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("main", &head, false).unwrap();
+
+        // A local main doesn't help while the checkout is still on master
+        let mismatch = show_default_branch_check(&repo, Ok("main".to_string())).unwrap();
+        assert!(mismatch.contains("on 'master'"));
+
+        repo.set_head("refs/heads/main").unwrap();
+        assert!(show_default_branch_check(&repo, Ok("main".to_string())).is_none());
+
+        repo.set_head_detached(head.id()).unwrap();
+        let mismatch = show_default_branch_check(&repo, Ok("main".to_string())).unwrap();
+        assert!(mismatch.contains("detached HEAD"));
+    }
+
+    #[test]
+    fn test_clone_repo() {
+        let (upstream_dir, upstream) = test_repo();
+        let head = upstream.head().unwrap().peel_to_commit().unwrap();
+        upstream.branch("next", &head, false).unwrap();
+        let url = upstream_dir.path().to_str().unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let credentials = CredentialCache::default();
+        let clone = credentials.clone_repo(url, &target.path().join("copy"), Some("next")).unwrap();
+        assert_eq!(clone.head().unwrap().shorthand(), Some("next"));
+
+        let err = credentials.clone_repo(url, &target.path().join("missing"), Some("nope")).map(|_| ()).unwrap_err();
+        assert!(is_missing_branch_error(&err));
+    }
+
+    #[test]
+    fn test_find_unopenable_repos_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path().join("healthy")).unwrap();
+        Repository::init(dir.path().join("group/nested/healthy")).unwrap();
+        let corrupt = dir.path().join("group/nested/corrupt");
+        fs::create_dir_all(corrupt.join(".git")).unwrap();
+
+        assert!(find_unopenable_repos(dir.path(), false).unwrap().is_empty());
+        let broken = find_unopenable_repos(dir.path(), true).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].0, corrupt);
+    }
+
+    #[test]
+    fn test_is_transient_fetch_error() {
+        let git_error = |code, class| anyhow::Error::from(git2::Error::new(code, class, "fetch failed"));
+
+        assert!(is_transient_fetch_error(&git_error(git2::ErrorCode::GenericError, git2::ErrorClass::Net)));
+        assert!(is_transient_fetch_error(&git_error(git2::ErrorCode::GenericError, git2::ErrorClass::Ssl)));
+        assert!(is_transient_fetch_error(&git_error(git2::ErrorCode::GenericError, git2::ErrorClass::Http)));
+
+        assert!(!is_transient_fetch_error(&git_error(git2::ErrorCode::Auth, git2::ErrorClass::Http)));
+        assert!(!is_transient_fetch_error(&git_error(git2::ErrorCode::Auth, git2::ErrorClass::Net)));
+        assert!(!is_transient_fetch_error(&git_error(git2::ErrorCode::NotFound, git2::ErrorClass::Reference)));
+        assert!(!is_transient_fetch_error(&git_error(git2::ErrorCode::NotFound, git2::ErrorClass::Repository)));
+        assert!(!is_transient_fetch_error(&anyhow!("repository not found")));
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{anyhow, Result};
+use clap::{Subcommand, ValueEnum};
+use colored::*;
+use console::style;
+use std::path::Path;
+
+use super::init::absolute;
+use crate::config::{config_path, write_settings, Config, SETTINGS};
+use crate::ui::BannerStyle;
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the value of a setting
+    Get {
+        /// Setting name, e.g. horizon_path
+        key: String,
+    },
+    /// Change a setting in the config file
+    Set {
+        /// Setting name, e.g. horizon_path
+        key: String,
+        value: String,
+    },
+    /// Show every setting and where the config file is
+    List,
+}
+
+impl ConfigCommand {
+    /// Whether stdout is reserved for machine-readable output (no banner or decoration)
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, ConfigCommand::Get { .. })
+    }
+}
+
+pub fn handle_command(cmd: ConfigCommand) -> Result<()> {
+    match cmd {
+        ConfigCommand::Get { key } => {
+            check_key(&key)?;
+            let value = Config::load()?.get(&key)
+                .ok_or_else(|| anyhow!("'{}' is not set", key))?;
+            // Raw value for scripts; bypasses the quiet mode
+            println!("{}", value);
+            Ok(())
+        }
+        ConfigCommand::Set { key, value } => set(&key, value),
+        ConfigCommand::List => list(),
+    }
+}
+
+fn check_key(key: &str) -> Result<()> {
+    if SETTINGS.iter().any(|(name, _)| *name == key) {
+        return Ok(());
+    }
+    let names: Vec<&str> = SETTINGS.iter().map(|(name, _)| *name).collect();
+    Err(anyhow!(
+        "Unknown setting '{}'; expected one of: {} ([templates] is edited in the file itself)",
+        key, names.join(", ")
+    ))
+}
+
+fn set(key: &str, value: String) -> Result<()> {
+    check_key(key)?;
+    let value = match key {
+        "horizon_path" => {
+            let path = absolute(Path::new(&value));
+            if !path.join("Cargo.toml").is_file() {
+                outln!("⚠️  {} doesn't look like a Horizon checkout yet; saving it anyway", path.display());
+            }
+            path.display().to_string()
+        }
+        "banner_style" => {
+            BannerStyle::from_str(&value, true)
+                .map_err(|_| anyhow!("Invalid banner_style '{}'; expected full, compact or none", value))?;
+            value.to_lowercase()
+        }
+        "github_token" => {
+            outln!("⚠️  The token is stored in plain text; github_token_command (e.g. \"gh auth token\") avoids that");
+            value
+        }
+        _ => value,
+    };
+
+    let path = config_path()?;
+    // A file holding github_token is made owner-only before the token is written
+    write_settings(&path, &[(key, value.clone())])?;
+    let shown = if key == "github_token" { mask(&value) } else { value };
+    outln!("✅ {} = {}", key, style(shown).cyan());
+    outln!("📄 Wrote {}", style(path.display()).yellow());
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let path = config_path()?;
+    let config = Config::load()?;
+
+    outln!("📄 Config file: {}{}", style(path.display()).yellow(), if path.exists() { "" } else { " (not created yet)" });
+    outln!();
+    for (key, description) in SETTINGS {
+        match config.get(key) {
+            Some(value) => {
+                let shown = if *key == "github_token" { mask(&value) } else { value };
+                outln!("{} = {}", key.bold(), style(shown).cyan());
+            }
+            None => outln!("{} {}", key.bold(), "(not set)".dimmed()),
+        }
+        outln!("  {}", description.dimmed());
+    }
+    if !config.templates.is_empty() {
+        outln!();
+        let names: Vec<&str> = config.templates.keys().map(String::as_str).collect();
+        outln!("📋 Templates: {}", names.join(", "));
+    }
+    Ok(())
+}
+
+/// Hide all but the last four characters of a secret
+fn mask(secret: &str) -> String {
+    let count = secret.chars().count();
+    if count <= 4 {
+        return "****".to_string();
+    }
+    format!("****{}", secret.chars().skip(count - 4).collect::<String>())
+}
@@ -0,0 +1,300 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Document};
+
+/// Settings read from the user's fbcli config file (e.g. ~/.config/fbcli/config.toml)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// GitHub REST API base URL, e.g. https://github.example.com/api/v3
+    pub github_api_base: Option<String>,
+    /// Host used for clone URLs, e.g. github.example.com
+    pub github_git_host: Option<String>,
+    /// Organization whose repositories the repo commands work with (default Far-Beyond-Dev)
+    pub github_org: Option<String>,
+    /// GitHub token, used when no token env var is set (before `github_token_command`)
+    pub github_token: Option<String>,
+    /// Command printing a GitHub token, used when no token env var is set (e.g. `gh auth token`)
+    pub github_token_command: Option<String>,
+    /// Horizon checkout that `plugin build` copies into when --horizon-path isn't given
+    pub horizon_path: Option<PathBuf>,
+    /// Banner printed before each command: full, compact or none
+    pub banner_style: Option<String>,
+    /// Template `plugin new` uses when --template isn't given: a template name or git URL
+    pub template_url: Option<String>,
+    /// Custom `plugin new` templates from the `[templates]` table, by name
+    pub templates: BTreeMap<String, TemplateConfig>,
+}
+
+/// A plugin template repository registered in the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateConfig {
+    pub url: String,
+    pub description: Option<String>,
+}
+
+impl Config {
+    /// Load the config file, or the defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let doc = contents.parse::<Document>()?;
+        Ok(Self {
+            github_api_base: string_key(&doc, "github_api_base")?,
+            github_git_host: string_key(&doc, "github_git_host")?,
+            github_org: string_key(&doc, "github_org")?,
+            github_token: string_key(&doc, "github_token")?,
+            github_token_command: string_key(&doc, "github_token_command")?,
+            horizon_path: string_key(&doc, "horizon_path")?.map(PathBuf::from),
+            banner_style: string_key(&doc, "banner_style")?,
+            template_url: string_key(&doc, "template_url")?,
+            templates: templates(&doc)?,
+        })
+    }
+}
+
+/// Top-level settings `fbcli config` can get and set, with a description for `config list`
+pub const SETTINGS: &[(&str, &str)] = &[
+    ("horizon_path", "Horizon checkout `plugin build` copies into"),
+    ("template_url", "Template `plugin new` uses by default (name or git URL)"),
+    ("github_org", "Organization the repo commands work with"),
+    ("github_token", "GitHub token, when no token environment variable is set"),
+    ("github_token_command", "Command printing a GitHub token (e.g. `gh auth token`)"),
+    ("github_api_base", "GitHub REST API base URL"),
+    ("github_git_host", "Host used for clone URLs"),
+    ("banner_style", "Banner before each command in a terminal: full, compact or none"),
+];
+
+impl Config {
+    /// Value of one of the `SETTINGS` keys, if set
+    pub fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "horizon_path" => self.horizon_path.as_ref().map(|path| path.display().to_string()),
+            "template_url" => self.template_url.clone(),
+            "github_org" => self.github_org.clone(),
+            "github_token" => self.github_token.clone(),
+            "github_token_command" => self.github_token_command.clone(),
+            "github_api_base" => self.github_api_base.clone(),
+            "github_git_host" => self.github_git_host.clone(),
+            "banner_style" => self.banner_style.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Read `[templates]`, where each entry is either a URL or `{ url = "...", description = "..." }`
+fn templates(doc: &Document) -> Result<BTreeMap<String, TemplateConfig>> {
+    let Some(item) = doc.get("templates") else {
+        return Ok(BTreeMap::new());
+    };
+    let table = item.as_table_like().ok_or_else(|| anyhow!("'templates' must be a table"))?;
+    table
+        .iter()
+        .map(|(name, entry)| {
+            let template = if let Some(url) = entry.as_str() {
+                TemplateConfig { url: url.to_string(), description: None }
+            } else if let Some(fields) = entry.as_table_like() {
+                let field = |key: &str| match fields.get(key) {
+                    None => Ok(None),
+                    Some(value) => value
+                        .as_str()
+                        .map(|value| Some(value.to_string()))
+                        .ok_or_else(|| anyhow!("'templates.{}.{}' must be a string", name, key)),
+                };
+                TemplateConfig {
+                    url: field("url")?.ok_or_else(|| anyhow!("'templates.{}' needs a url", name))?,
+                    description: field("description")?,
+                }
+            } else {
+                return Err(anyhow!("'templates.{}' must be a URL or a table with a url", name));
+            };
+            Ok((name.to_string(), template))
+        })
+        .collect()
+}
+
+fn string_key(doc: &Document, key: &str) -> Result<Option<String>> {
+    match doc.get(key) {
+        None => Ok(None),
+        Some(item) => item
+            .as_str()
+            .map(|value| Some(value.to_string()))
+            .ok_or_else(|| anyhow!("'{}' must be a string", key)),
+    }
+}
+
+/// Set top-level string keys in a config file, creating it if needed and
+/// keeping every other setting and comment as it was
+pub fn write_settings(path: &Path, settings: &[(&str, String)]) -> Result<()> {
+    let contents = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut doc = contents.parse::<Document>().with_context(|| format!("Invalid config file {}", path.display()))?;
+    for (key, setting) in settings {
+        doc[key] = value(setting.as_str());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    write_config(path, &doc)
+}
+
+/// Remove top-level keys from a config file, keeping everything else as it was.
+/// Missing keys and a missing file are not errors.
+pub fn remove_settings(path: &Path, keys: &[&str]) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut doc = contents.parse::<Document>().with_context(|| format!("Invalid config file {}", path.display()))?;
+    for key in keys {
+        doc.remove(key);
+    }
+    write_config(path, &doc)
+}
+
+/// Keys holding secrets; a config file that contains one is readable by its owner only
+const SECRET_KEYS: &[&str] = &["github_token"];
+
+/// Write the config file. When it holds a secret, the file is restricted to
+/// the owner before anything is written, so the secret is never exposed.
+fn write_config(path: &Path, doc: &Document) -> Result<()> {
+    let private = SECRET_KEYS.iter().any(|key| doc.contains_key(key));
+    let write = || -> std::io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true);
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        // Truncated only once the permissions are right, so a failure leaves the old contents
+        let mut file = options.open(path)?;
+        #[cfg(unix)]
+        if private {
+            // `mode` only applies to a newly created file
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.set_len(0)?;
+        file.write_all(doc.to_string().as_bytes())
+    };
+    write().with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Location of the fbcli config file
+pub fn config_path() -> Result<PathBuf> {
+    let base = dirs::config_dir().ok_or_else(|| anyhow!("Could not determine the user config directory"))?;
+    Ok(base.join("fbcli").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "github_api_base = \"https://github.example.com/api/v3\"\ngithub_git_host = \"github.example.com\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.github_api_base.as_deref(), Some("https://github.example.com/api/v3"));
+        assert_eq!(config.github_git_host.as_deref(), Some("github.example.com"));
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        let config = Config::parse("template_url = \"builtin\"\nhorizon_path = \"/src/Horizon\"\n").unwrap();
+        assert_eq!(config.get("template_url").as_deref(), Some("builtin"));
+        assert_eq!(config.get("horizon_path").as_deref(), Some("/src/Horizon"));
+        assert_eq!(config.get("github_token"), None);
+        assert!(Config::parse("github_git_host = 3").is_err());
+    }
+
+    #[test]
+    fn test_write_settings_keeps_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fbcli/config.toml");
+        write_settings(&path, &[("github_org", "acme".to_string())]).unwrap();
+        let existing = fs::read_to_string(&path).unwrap();
+        fs::write(&path, format!("# my settings\n{}\n[templates]\nteam = \"https://git.acme.dev/t.git\"\n", existing)).unwrap();
+
+        write_settings(&path, &[("horizon_path", "/src/Horizon".to_string()), ("github_org", "beta".to_string())]).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# my settings"));
+        let config = Config::parse(&contents).unwrap();
+        assert_eq!(config.github_org.as_deref(), Some("beta"));
+        assert_eq!(config.horizon_path, Some(PathBuf::from("/src/Horizon")));
+        assert!(config.templates.contains_key("team"));
+    }
+
+    #[test]
+    fn test_remove_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        remove_settings(&path, &["horizon_path"]).unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, "# mine\ngithub_org = \"acme\"\nhorizon_path = \"/src/Horizon\"\n").unwrap();
+        remove_settings(&path, &["horizon_path", "github_token_command"]).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# mine"));
+        let config = Config::parse(&contents).unwrap();
+        assert_eq!(config.horizon_path, None);
+        assert_eq!(config.github_org.as_deref(), Some("acme"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_settings_restricts_token_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let new_file = dir.path().join("new.toml");
+        write_settings(&new_file, &[("github_token", "ghp_secret".to_string())]).unwrap();
+        assert_eq!(mode(&new_file), 0o600);
+
+        let existing = dir.path().join("existing.toml");
+        fs::write(&existing, "github_org = \"acme\"\n").unwrap();
+        fs::set_permissions(&existing, fs::Permissions::from_mode(0o644)).unwrap();
+        write_settings(&existing, &[("github_token", "ghp_secret".to_string())]).unwrap();
+        assert_eq!(mode(&existing), 0o600);
+        let config = Config::parse(&fs::read_to_string(&existing).unwrap()).unwrap();
+        assert_eq!(config.github_org.as_deref(), Some("acme"));
+        assert_eq!(config.github_token.as_deref(), Some("ghp_secret"));
+
+        // Files without a token keep the usual permissions
+        let plain = dir.path().join("plain.toml");
+        fs::write(&plain, "").unwrap();
+        fs::set_permissions(&plain, fs::Permissions::from_mode(0o644)).unwrap();
+        write_settings(&plain, &[("github_org", "acme".to_string())]).unwrap();
+        assert_eq!(mode(&plain), 0o644);
+    }
+
+    #[test]
+    fn test_parse_templates() {
+        let config = Config::parse(
+            "[templates]\nteam = \"https://git.acme.dev/team/plugin-template.git\"\n\
+             \n[templates.chat]\nurl = \"git@git.acme.dev:team/chat-template.git\"\ndescription = \"Chat plugin skeleton\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.templates["team"].url, "https://git.acme.dev/team/plugin-template.git");
+        assert_eq!(config.templates["team"].description, None);
+        assert_eq!(config.templates["chat"].description.as_deref(), Some("Chat plugin skeleton"));
+
+        assert!(Config::parse("[templates.chat]\ndescription = \"no url\"\n").is_err());
+        assert!(Config::parse("templates = 3").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{anyhow, Context};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, Semaphore};
+
+/// Default for `--max-concurrent-requests`
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+/// Minimum gap between the starts of two GitHub API requests
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+/// How many times a rate-limited request is retried
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Longest fbcli waits out a rate limit before giving up and reporting it
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// Reported for a 401: the token is the only credential fbcli sends to the API
+pub const TOKEN_REJECTED: &str = "GitHub rejected the configured token (401); check FBCLI_GITHUB_TOKEN / GITHUB_TOKEN or github_token_command";
+
+/// Shared limiter for every GitHub API request fbcli makes
+struct RateLimiter {
+    permits: Semaphore,
+    last_start: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(max_concurrent: usize) -> Self {
+        Self { permits: Semaphore::new(max_concurrent.max(1)), last_start: Mutex::new(None) }
+    }
+
+    /// Wait until at least `MIN_REQUEST_INTERVAL` has passed since the last request started
+    async fn wait_turn(&self) {
+        let mut last_start = self.last_start.lock().await;
+        if let Some(last) = *last_start {
+            let elapsed = last.elapsed();
+            if elapsed < MIN_REQUEST_INTERVAL {
+                tokio::time::sleep(MIN_REQUEST_INTERVAL - elapsed).await;
+            }
+        }
+        *last_start = Some(Instant::now());
+    }
+}
+
+static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Set how many GitHub API requests may be in flight at once. Only the
+/// first call (before any request is sent) has an effect.
+pub fn configure(max_concurrent: usize) {
+    let _ = LIMITER.set(RateLimiter::new(max_concurrent));
+}
+
+fn limiter() -> &'static RateLimiter {
+    LIMITER.get_or_init(|| RateLimiter::new(DEFAULT_MAX_CONCURRENT_REQUESTS))
+}
+
+/// Send a GitHub API request through the shared limiter. Secondary rate
+/// limits (403/429 with Retry-After or an exhausted quota) are waited out a
+/// few times; anything else, including a limit that resets too far in the
+/// future, is returned to the caller as-is.
+pub async fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let limiter = limiter();
+    let _permit = limiter.permits.acquire().await.expect("GitHub request semaphore is never closed");
+
+    let mut request = request;
+    let mut retries = 0;
+    loop {
+        limiter.wait_turn().await;
+        let retry = request.try_clone();
+        let response = request.send().await?;
+
+        match (rate_limit_wait(&response), retry) {
+            (Some(wait), Some(retry)) if retries < MAX_RATE_LIMIT_RETRIES => {
+                let message = format!("⏳ GitHub rate limit hit; retrying in {}s", wait.as_secs_f64().ceil());
+                if crate::ui::is_quiet() {
+                    // stdout is machine-readable; keep the notice visible on stderr
+                    eprintln!("{}", crate::ui::render(&message));
+                } else {
+                    outln!("{}", message);
+                }
+                tokio::time::sleep(wait).await;
+                request = retry;
+                retries += 1;
+            }
+            _ => return Ok(response),
+        }
+    }
+}
+
+/// Fetch every page of a GitHub list endpoint, following the `Link` header's
+/// `rel="next"` URL until the last page. `request` builds the GET for each page's URL.
+pub async fn get_all_pages<T: DeserializeOwned>(
+    url: &str,
+    request: impl Fn(&str) -> RequestBuilder,
+) -> anyhow::Result<Vec<T>> {
+    let mut items = Vec::new();
+    for_each_page(url, request, |page| {
+        items.extend(page);
+        Ok(())
+    })
+    .await?;
+    Ok(items)
+}
+
+/// Like `get_all_pages`, but hands each page to `on_page` as soon as it
+/// arrives instead of collecting them, for output that streams
+pub async fn for_each_page<T: DeserializeOwned>(
+    url: &str,
+    request: impl Fn(&str) -> RequestBuilder,
+    mut on_page: impl FnMut(Vec<T>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut next = Some(url.to_string());
+    while let Some(url) = next {
+        let response = send(request(&url))
+            .await
+            .with_context(|| format!("Failed to fetch {}", url))?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(anyhow!(TOKEN_REJECTED));
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("GitHub API request failed: {} ({})", response.status(), url));
+        }
+        next = response.headers().get(reqwest::header::LINK)
+            .and_then(|v| v.to_str().ok())
+            .and_then(next_page_url);
+        let page: Vec<T> = response.json().await.context("Failed to parse GitHub API response")?;
+        on_page(page)?;
+    }
+    Ok(())
+}
+
+/// The `rel="next"` target of a `Link` header, e.g. `<https://...&page=2>; rel="next", <...>; rel="last"`
+fn next_page_url(link: &str) -> Option<String> {
+    link.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        let is_next = params.split(';').any(|param| param.trim().trim_start_matches("rel=").trim_matches('"') == "next");
+        is_next.then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// How long GitHub asked us to back off, if the response is a rate limit
+fn rate_limit_wait(response: &Response) -> Option<Duration> {
+    let status = response.status();
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::FORBIDDEN {
+        return None;
+    }
+    let header = |name: &str| {
+        response.headers().get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+
+    let wait = if let Some(seconds) = header("retry-after") {
+        Duration::from_secs(seconds)
+    } else if header("x-ratelimit-remaining") == Some(0) {
+        // Primary limit: wait until the quota resets
+        let reset = header("x-ratelimit-reset")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Duration::from_secs(reset.saturating_sub(now))
+    } else {
+        // A plain 403 is a permissions problem, not a rate limit
+        return None;
+    };
+    (wait <= MAX_RATE_LIMIT_WAIT).then_some(wait)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Write};
+    use std::net::TcpListener;
+
+    /// Answer one request per canned response, in order
+    fn serve(listener: TcpListener, responses: Vec<String>) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_send_honors_retry_after() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = serve(listener, vec![
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]".to_string(),
+        ]);
+
+        let client = reqwest::Client::new();
+        let response = send(client.get(format!("http://{}/orgs/x/repos", addr))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "[]");
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_all_pages_follows_link_header() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let page = |link: &str, body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                link,
+                body.len(),
+                body
+            )
+        };
+        let server = serve(listener, vec![
+            page(&format!("Link: <http://{addr}/repos?page=2>; rel=\"next\", <http://{addr}/repos?page=2>; rel=\"last\"\r\n"), "[1, 2]"),
+            page(&format!("Link: <http://{addr}/repos?page=1>; rel=\"prev\", <http://{addr}/repos?page=1>; rel=\"first\"\r\n"), "[3]"),
+        ]);
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/repos?page=1", addr);
+        let items: Vec<u32> = get_all_pages(&url, |url| client.get(url)).await.unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_for_each_page_yields_pages_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = serve(listener, vec![
+            format!("HTTP/1.1 200 OK\r\nLink: <http://{addr}/repos?page=2>; rel=\"next\"\r\nContent-Length: 6\r\nConnection: close\r\n\r\n[1, 2]"),
+            "HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\n[3]".to_string(),
+        ]);
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/repos?page=1", addr);
+        let mut pages: Vec<Vec<u32>> = Vec::new();
+        for_each_page(&url, |url| client.get(url), |page| {
+            pages.push(page);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(pages, vec![vec![1, 2], vec![3]]);
+        server.join().unwrap();
+    }
+}
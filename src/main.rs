@@ -1,20 +1,57 @@
-use clap::{Parser, Subcommand};
+use clap::builder::FalseyValueParser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use anyhow::Result;
-use colored::*;
+use std::io::IsTerminal;
 
+#[macro_use]
+mod ui;
 mod commands;
+mod config;
+mod github;
+mod summary;
 mod utils;
 
-use commands::{horizon, repo};
+use commands::{doctor, horizon, init, repo, settings};
 
 #[derive(Parser)]
 #[command(
     name = "fbcli",
     about = "Far Beyond Development Kit - CLI tool for Horizon plugin development and repo management",
-    version = "0.1.0",
+    version,
     long_about = None
 )]
 struct Cli {
+    /// Use ASCII markers instead of emoji and disable colors
+    #[arg(long, global = true, env = "FBCLI_PLAIN", value_parser = FalseyValueParser::new())]
+    plain: bool,
+
+    /// Maximum number of GitHub API requests in flight at once
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        env = "FBCLI_MAX_CONCURRENT_REQUESTS",
+        default_value_t = github::DEFAULT_MAX_CONCURRENT_REQUESTS
+    )]
+    max_concurrent_requests: usize,
+
+    /// Print diagnostics ([DEBUG] lines) and cargo's live output
+    #[arg(long, short = 'v', global = true, conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Print only results, warnings and errors: no banner, progress bars or status lines
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+
+    /// Banner printed before each command (default: full in a terminal, none otherwise)
+    #[arg(long, global = true, value_name = "STYLE", env = "FBCLI_BANNER_STYLE")]
+    banner_style: Option<ui::BannerStyle>,
+
+    /// Don't print the banner (same as --banner-style none)
+    #[arg(long, global = true)]
+    no_banner: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -28,19 +65,114 @@ enum Commands {
     /// Repository management commands
     #[command(subcommand)]
     Repo(repo::RepoCommand),
+
+    /// Write a starter config file and set up a workspace directory
+    Init(init::InitArgs),
+
+    /// Check that git, cargo, Horizon and GitHub are available
+    Doctor,
+
+    /// Get, set or list settings in the config file
+    #[command(subcommand)]
+    Config(settings::ConfigCommand),
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Show version information (with --verbose: git commit, compiler, target and libgit2 version)
+    Version,
+}
+
+impl Commands {
+    /// Whether stdout is reserved for machine-readable output (no banner or decoration)
+    fn is_machine_readable(&self) -> bool {
+        match self {
+            Commands::Repo(cmd) => cmd.is_machine_readable(),
+            Commands::Horizon(cmd) => cmd.is_machine_readable(),
+            Commands::Config(cmd) => cmd.is_machine_readable(),
+            Commands::Completions { .. } => true,
+            Commands::Init(_) | Commands::Doctor | Commands::Version => false,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    ui::set_plain(cli.plain);
+    ui::set_quiet(cli.command.is_machine_readable());
+    ui::set_brief(cli.quiet);
+    ui::set_verbose(cli.verbose);
+    github::configure(cli.max_concurrent_requests);
+
+    if !cli.quiet && !cli.no_banner {
+        ui::print_banner(banner_style(cli.banner_style));
+    }
 
-    // Print welcome banner
-    println!("{}", "🚀 Far Beyond Development Kit".bright_cyan().bold());
-    println!("{}", "════════════════════════════".bright_cyan());
-    println!();
+    // Relative paths fail with a bare "No such file or directory" once the
+    // working directory is deleted, so say what's actually wrong up front
+    if !matches!(cli.command, Commands::Completions { .. } | Commands::Version) {
+        utils::current_dir()?;
+    }
 
     match cli.command {
         Commands::Horizon(cmd) => horizon::handle_command(cmd).await,
         Commands::Repo(cmd) => repo::handle_command(cmd).await,
+        Commands::Init(args) => init::run(args),
+        Commands::Doctor => doctor::run().await,
+        Commands::Config(cmd) => settings::handle_command(cmd),
+        Commands::Completions { shell } => {
+            // The script is the whole output, so it bypasses the quiet-mode macros
+            clap_complete::generate(shell, &mut Cli::command(), "fbcli", &mut std::io::stdout());
+            Ok(())
+        }
+        Commands::Version => {
+            print_version(cli.verbose);
+            Ok(())
+        }
+    }
+}
+
+/// The flag (or env var) wins, then `banner_style` in the config file; without
+/// either the full banner is only shown when stdout is a terminal
+fn banner_style(flag: Option<ui::BannerStyle>) -> ui::BannerStyle {
+    if let Some(style) = flag {
+        return style;
     }
+    // Piped output and CI logs get no banner unless it's asked for explicitly
+    if !std::io::stdout().is_terminal() {
+        return ui::BannerStyle::None;
+    }
+    // A broken config file is reported by the commands that need it, not here,
+    // and a bad value only costs the banner, never the command
+    if let Some(configured) = config::Config::load().ok().and_then(|config| config.banner_style) {
+        return ui::BannerStyle::from_str(&configured, true).unwrap_or_else(|_| {
+            eprintln!(
+                "{}",
+                ui::render(&format!(
+                    "⚠️  Invalid banner_style '{}' in the config file; expected full, compact or none",
+                    configured
+                ))
+            );
+            ui::BannerStyle::Full
+        });
+    }
+    ui::BannerStyle::Full
+}
+
+fn print_version(verbose: bool) {
+    outln!("fbcli {}", env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return;
+    }
+
+    let libgit2 = git2::Version::get().libgit2_version();
+    outln!("commit:  {}", env!("FBCLI_GIT_COMMIT"));
+    outln!("rustc:   {}", env!("FBCLI_RUSTC_VERSION"));
+    outln!("target:  {}", env!("FBCLI_TARGET"));
+    outln!("libgit2: {}.{}.{}", libgit2.0, libgit2.1, libgit2.2);
 }
\ No newline at end of file
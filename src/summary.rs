@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Result of processing a single item in a batch command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeStatus {
+    /// The item was processed and changed
+    Success,
+    /// The item was processed but nothing needed to change
+    Unchanged,
+    /// The item was intentionally not processed
+    Skipped,
+    /// Processing the item failed
+    Failed,
+}
+
+/// Per-item entry in a batch summary
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemOutcome {
+    pub name: String,
+    pub path: PathBuf,
+    pub status: OutcomeStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ItemOutcome {
+    pub fn new(path: &Path, status: OutcomeStatus) -> Self {
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        Self { name, path: path.to_path_buf(), status, message: None }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Aggregate counts over all items in a batch
+#[derive(Debug, Default, Serialize)]
+pub struct OutcomeCounts {
+    pub total: usize,
+    pub success: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Structured end-of-run report for batch commands (`--summary-json`)
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub command: String,
+    pub items: Vec<ItemOutcome>,
+    pub counts: OutcomeCounts,
+}
+
+impl BatchSummary {
+    pub fn new(command: &str) -> Self {
+        Self { command: command.to_string(), items: Vec::new(), counts: OutcomeCounts::default() }
+    }
+
+    pub fn push(&mut self, item: ItemOutcome) {
+        self.counts.total += 1;
+        match item.status {
+            OutcomeStatus::Success => self.counts.success += 1,
+            OutcomeStatus::Unchanged => self.counts.unchanged += 1,
+            OutcomeStatus::Skipped => self.counts.skipped += 1,
+            OutcomeStatus::Failed => self.counts.failed += 1,
+        }
+        self.items.push(item);
+    }
+
+    /// Write the summary as pretty-printed JSON, if a path was requested
+    pub fn write_if_requested(&self, path: Option<&Path>) -> Result<()> {
+        let Some(path) = path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write summary to {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_summary_counts() {
+        let mut summary = BatchSummary::new("repo update");
+        summary.push(ItemOutcome::new(Path::new("a/Horizon"), OutcomeStatus::Success));
+        summary.push(ItemOutcome::new(Path::new("a/fbcli"), OutcomeStatus::Unchanged));
+        summary.push(ItemOutcome::new(Path::new("a/docs"), OutcomeStatus::Failed).with_message("boom"));
+
+        assert_eq!(summary.counts.total, 3);
+        assert_eq!(summary.counts.success, 1);
+        assert_eq!(summary.counts.unchanged, 1);
+        assert_eq!(summary.counts.failed, 1);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["items"][0]["name"], "Horizon");
+        assert_eq!(json["items"][2]["status"], "failed");
+        assert_eq!(json["items"][2]["message"], "boom");
+        assert!(json["items"][0].get("message").is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static BRIEF: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Emoji with a meaningful status, mapped to ASCII markers in plain mode
+const MARKERS: &[(&str, &str)] = &[
+    ("✅", "[OK]"),
+    ("🎉", "[OK]"),
+    ("❌", "[FAIL]"),
+    ("⚠️", "[WARN]"),
+    ("⏳", "[WAIT]"),
+];
+
+/// Decorative symbols, replaced (or dropped when empty) in plain mode
+const DECORATIONS: &[(&str, &str)] = &[
+    ("▶", "-"),
+    ("•", "-"),
+    ("═", "="),
+    ("ℹ", "-"),
+    ("🚀", ""), ("🔧", ""), ("📂", ""), ("📁", ""), ("🔨", ""), ("📄", ""),
+    ("📋", ""), ("📦", ""), ("🔒", ""), ("🌐", ""), ("🔗", ""), ("📥", ""),
+    ("🔄", ""), ("🔍", ""), ("📊", ""), ("🌿", ""), ("⬆️", ""), ("⬇️", ""),
+    ("📈", ""), ("🏷️", ""), ("📝", ""), ("💾", ""),
+];
+
+/// Enable or disable plain output (ASCII markers, no colors)
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+    if plain {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Suppress all `outln!`/`out!` output, for commands whose stdout is machine-readable
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `--quiet`: drop the banner, progress bars and `statusln!` narration,
+/// keeping results, warnings and errors
+pub fn set_brief(brief: bool) {
+    BRIEF.store(brief, Ordering::Relaxed);
+}
+
+pub fn is_brief() -> bool {
+    BRIEF.load(Ordering::Relaxed)
+}
+
+/// `--verbose`: also print `debugln!` diagnostics and live tool output
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Whether progress bars and spinners should be drawn
+pub fn shows_progress() -> bool {
+    !is_quiet() && !is_brief()
+}
+
+/// Prepare a line for output, applying plain mode if enabled
+pub fn render(text: &str) -> String {
+    if is_plain() {
+        to_plain(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Replace emoji with ASCII markers and drop purely decorative symbols
+fn to_plain(text: &str) -> String {
+    let mut out = text.to_string();
+    for (emoji, marker) in MARKERS {
+        out = out.replace(emoji, marker);
+    }
+    for (symbol, replacement) in DECORATIONS {
+        if replacement.is_empty() {
+            // Also swallow the spacing that followed the symbol
+            out = out.replace(&format!("{}  ", symbol), "");
+            out = out.replace(&format!("{} ", symbol), "");
+        }
+        out = out.replace(symbol, replacement);
+    }
+    // Strip stray emoji variation selectors
+    out.replace('\u{FE0F}', "")
+}
+
+/// `println!` that respects plain and quiet output modes
+macro_rules! outln {
+    () => {
+        if !$crate::ui::is_quiet() {
+            println!()
+        }
+    };
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_quiet() {
+            println!("{}", $crate::ui::render(&format!($($arg)*)))
+        }
+    };
+}
+
+/// `outln!` for progress narration ("Scanning...", "Building..."), hidden by `--quiet`
+macro_rules! statusln {
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_brief() {
+            outln!($($arg)*)
+        }
+    };
+}
+
+/// `outln!` for diagnostics, prefixed with `[DEBUG]` and only shown with `--verbose`
+macro_rules! debugln {
+    ($($arg:tt)*) => {
+        if $crate::ui::is_verbose() {
+            outln!("[DEBUG] {}", format!($($arg)*))
+        }
+    };
+}
+
+/// `print!` that respects plain and quiet output modes
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if !$crate::ui::is_quiet() {
+            print!("{}", $crate::ui::render(&format!($($arg)*)))
+        }
+    };
+}
+
+/// How much of the welcome banner to print before a command's output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum BannerStyle {
+    /// Title with an underline
+    Full,
+    /// A single line
+    Compact,
+    /// No banner
+    None,
+}
+
+pub fn print_banner(style: BannerStyle) {
+    use colored::Colorize;
+    match style {
+        BannerStyle::Full => {
+            outln!("{}", "🚀 Far Beyond Development Kit".bright_cyan().bold());
+            outln!("{}", "════════════════════════════".bright_cyan());
+            outln!();
+        }
+        BannerStyle::Compact => outln!("{}", format!("🚀 fbcli {}", env!("CARGO_PKG_VERSION")).bright_cyan()),
+        BannerStyle::None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plain() {
+        assert_eq!(to_plain("✅ Updated"), "[OK] Updated");
+        assert_eq!(to_plain("❌ Failed: boom"), "[FAIL] Failed: boom");
+        assert_eq!(to_plain("  ⚠️  Uncommitted changes"), "  [WARN]  Uncommitted changes");
+        assert_eq!(to_plain("📂 Target directory: x"), "Target directory: x");
+        assert_eq!(to_plain("  ⬆️  2 commits ahead"), "  2 commits ahead");
+        assert_eq!(to_plain("▶ Horizon"), "- Horizon");
+        assert_eq!(to_plain("════"), "====");
+        assert_eq!(to_plain("plain text"), "plain text");
+    }
+}
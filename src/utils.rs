@@ -1,6 +1,14 @@
-use anyhow::{anyhow, Result};
-use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use console::measure_text_width;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::config::Config;
 
 /// Check if a command exists in PATH
 pub fn command_exists(command: &str) -> bool {
@@ -15,6 +23,24 @@ pub fn check_git_available() -> Result<()> {
     Ok(())
 }
 
+/// Installed git version as (major, minor, patch)
+pub fn git_version() -> Result<(u32, u32, u32)> {
+    check_git_available()?;
+    let output = Command::new("git").arg("--version").output()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    parse_git_version(&text).ok_or_else(|| anyhow!("Could not parse git version from '{}'", text.trim()))
+}
+
+/// Parse `git --version` output, e.g. "git version 2.39.3 (Apple Git-145)"
+pub fn parse_git_version(output: &str) -> Option<(u32, u32, u32)> {
+    let version = output.trim().strip_prefix("git version ")?.split_whitespace().next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
 /// Check if cargo is available
 pub fn check_cargo_available() -> Result<()> {
     if !command_exists("cargo") {
@@ -23,40 +49,164 @@ pub fn check_cargo_available() -> Result<()> {
     Ok(())
 }
 
-/// Check if we're in a git repository
-pub fn is_git_repository(path: &Path) -> bool {
-    path.join(".git").exists()
+/// Get the GitHub token from FBCLI_GITHUB_TOKEN or GITHUB_TOKEN, or else from
+/// the config file's `github_token` or the output of its `github_token_command`
+pub fn github_token() -> Option<String> {
+    // The command may be slow (e.g. a password manager), so only run it once
+    static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+    TOKEN
+        .get_or_init(|| {
+            ["FBCLI_GITHUB_TOKEN", "GITHUB_TOKEN"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .map(|token| token.trim().to_string())
+                .find(|token| !token.is_empty())
+                .or_else(|| Config::load().ok()?.github_token.filter(|token| !token.trim().is_empty()))
+                .or_else(token_from_command)
+        })
+        .clone()
 }
 
-/// Check if we're in a Rust project
-pub fn is_rust_project(path: &Path) -> bool {
-    path.join("Cargo.toml").exists()
+/// Run `github_token_command` from the config file and use its output as the token
+fn token_from_command() -> Option<String> {
+    let command = Config::load().ok()?.github_token_command?;
+    let mut parts = command.split_whitespace();
+    let output = Command::new(parts.next()?).args(parts).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!token.is_empty()).then_some(token)
 }
 
-/// Get the current git branch name
-pub fn get_current_branch(repo_path: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .args(["branch", "--show-current"])
-        .current_dir(repo_path)
-        .output()?;
+/// Owner and repository parsed from a GitHub remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubRemote {
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
 
-    if !output.status.success() {
-        return Err(anyhow!("Failed to get current branch"));
+impl GitHubRemote {
+    pub fn https_url(&self) -> String {
+        format!("https://{}/{}/{}.git", self.host, self.owner, self.repo)
     }
 
-    let branch = String::from_utf8(output.stdout)?
-        .trim()
-        .to_string();
+    pub fn ssh_url(&self) -> String {
+        format!("git@{}:{}/{}.git", self.host, self.owner, self.repo)
+    }
 
-    if branch.is_empty() {
-        return Err(anyhow!("No current branch found"));
+    /// The repository's page in the browser
+    pub fn web_url(&self) -> String {
+        format!("https://{}/{}/{}", self.host, self.owner, self.repo)
     }
+}
+
+/// Parse HTTPS, scp-style SSH, and ssh:// remote URLs into owner/repo
+pub fn parse_github_remote(url: &str) -> Option<GitHubRemote> {
+    let url = url.trim();
+    let rest = if let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        rest
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        rest
+    } else if let Some((user_host, path)) = url.split_once(':') {
+        // scp-style: git@github.com:Owner/Repo.git
+        if user_host.contains('/') {
+            return None;
+        }
+        return split_remote_path(user_host, path);
+    } else {
+        return None;
+    };
 
-    Ok(branch)
+    let (authority, path) = rest.split_once('/')?;
+    split_remote_path(authority, path)
+}
+
+fn split_remote_path(authority: &str, path: &str) -> Option<GitHubRemote> {
+    // Drop any user@ prefix and :port suffix
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+
+    let mut parts = path.trim_matches('/').splitn(2, '/');
+    let owner = parts.next()?;
+    let repo = parts.next()?.trim_end_matches('/');
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+
+    if host.is_empty() || owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some(GitHubRemote { host: host.to_string(), owner: owner.to_string(), repo: repo.to_string() })
+}
+
+/// Open a URL with the platform's default handler
+pub fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` expects first
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(url).status()
+        .with_context(|| format!("Failed to launch a browser; open {} manually", url))?;
+    if !status.success() {
+        return Err(anyhow!("Browser launcher exited with {}; open {} manually", status, url));
+    }
+    Ok(())
+}
+
+/// Check if we're in a git repository
+pub fn is_git_repository(path: &Path) -> bool {
+    path.join(".git").exists()
+}
+
+/// Ask a yes/no question on stdin; anything other than y/yes counts as no
+pub fn confirm(prompt: &str) -> Result<bool> {
+    out!("{} [y/N] ", prompt);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// The working directory, with a clear error when it was deleted (or unmounted)
+/// out from under the shell instead of the raw "No such file or directory"
+pub fn current_dir() -> Result<PathBuf> {
+    std::env::current_dir().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => anyhow!("The current directory no longer exists; cd to a valid directory and try again"),
+        _ => anyhow!("Cannot determine the current directory: {}", e),
+    })
+}
+
+/// Per-user cache directory for fbcli (e.g. ~/.cache/fbcli)
+pub fn cache_dir() -> Result<PathBuf> {
+    let base = dirs::cache_dir().ok_or_else(|| anyhow!("Could not determine the user cache directory"))?;
+    Ok(base.join("fbcli"))
+}
+
+/// Check if we're in a Rust project
+pub fn is_rust_project(path: &Path) -> bool {
+    path.join("Cargo.toml").exists()
 }
 
 /// Validate plugin name
 pub fn validate_plugin_name(name: &str) -> Result<()> {
+    match plugin_name_problems(name)?.into_iter().next() {
+        Some(problem) => Err(anyhow!(problem)),
+        None => Ok(()),
+    }
+}
+
+/// Check a plugin name. Anything cargo or the generated code can't accept is an
+/// error: characters that can't appear in a directory or crate name, a leading
+/// hyphen (cargo rejects the package) and a leading digit (the `{Name}Plugin`
+/// struct wouldn't be an identifier). Breaches of fbcli's naming conventions
+/// are returned so `plugin new --force-name` can report them as warnings instead.
+pub fn plugin_name_problems(name: &str) -> Result<Vec<String>> {
     if name.is_empty() {
         return Err(anyhow!("Plugin name cannot be empty"));
     }
@@ -65,11 +215,38 @@ pub fn validate_plugin_name(name: &str) -> Result<()> {
         return Err(anyhow!("Plugin name can only contain alphanumeric characters, underscores, and hyphens"));
     }
 
-    if name.starts_with('-') || name.starts_with('_') {
-        return Err(anyhow!("Plugin name cannot start with a hyphen or underscore"));
+    if name.starts_with('-') {
+        return Err(anyhow!("Plugin name cannot start with a hyphen"));
     }
 
-    Ok(())
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(anyhow!("Plugin name cannot start with a digit"));
+    }
+
+    let mut problems = Vec::new();
+    if name.starts_with('_') {
+        problems.push("Plugin name should not start with an underscore".to_string());
+    }
+    if name.starts_with("plugin_") || name.starts_with("plugin-") {
+        problems.push(format!(
+            "Plugin name should not include the plugin_ prefix; fbcli adds it, so the package would be named plugin_{}",
+            name.replace('-', "_")
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Recursively compute the total size of all files under a path
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let mut total = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
 }
 
 /// Format bytes as human readable string
@@ -90,6 +267,73 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Hex-encoded SHA-256 digest of a file's contents
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Describe how long ago a timestamp was, e.g. "3 days ago"
+pub fn format_relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - then).num_seconds();
+    if seconds < 60 {
+        return "just now".to_string();
+    }
+
+    const UNITS: &[(i64, &str)] = &[
+        (60 * 60 * 24 * 365, "year"),
+        (60 * 60 * 24 * 30, "month"),
+        (60 * 60 * 24 * 7, "week"),
+        (60 * 60 * 24, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+    let (size, unit) = UNITS.iter()
+        .find(|(size, _)| seconds >= *size)
+        .copied()
+        .unwrap_or((60, "minute"));
+    let count = seconds / size;
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// Render rows as a left-aligned text table with a header and separator line
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| measure_text_width(h)).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() {
+                widths[i] = widths[i].max(measure_text_width(cell));
+            }
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - measure_text_width(cell))))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(headers.to_vec())];
+    lines.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+    for row in rows {
+        lines.push(format_row(row.iter().map(String::as_str).collect()));
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,6 +350,83 @@ mod tests {
         assert!(validate_plugin_name("-plugin").is_err());
         assert!(validate_plugin_name("my plugin").is_err());
         assert!(validate_plugin_name("my@plugin").is_err());
+
+        assert!(validate_plugin_name("9lives").is_err());
+        assert!(validate_plugin_name("plugin_chat").is_err());
+
+        assert!(plugin_name_problems("my_plugin").unwrap().is_empty());
+        assert_eq!(plugin_name_problems("_plugin").unwrap().len(), 1);
+        assert_eq!(plugin_name_problems("plugin-chat").unwrap().len(), 1);
+        assert!(plugin_name_problems("my/plugin").is_err());
+        assert!(plugin_name_problems("-chat").is_err());
+        assert!(plugin_name_problems("9lives").is_err());
+    }
+
+    #[test]
+    fn test_dir_size() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir_all(dir.path().join("nested/deeper")).unwrap();
+        std::fs::write(dir.path().join("nested/b.bin"), vec![0u8; 1024]).unwrap();
+        std::fs::write(dir.path().join("nested/deeper/c.bin"), vec![0u8; 4000]).unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 5124);
+        assert_eq!(dir_size(&dir.path().join("nested")).unwrap(), 5024);
+        assert_eq!(dir_size(&dir.path().join("a.txt")).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_dir_size_empty_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(dir_size(dir.path()).unwrap(), 0);
+        assert!(dir_size(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = Utc::now();
+        assert_eq!(format_relative_time(now, now), "just now");
+        assert_eq!(format_relative_time(now - chrono::Duration::minutes(1), now), "1 minute ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::hours(5), now), "5 hours ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::days(3), now), "3 days ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::days(15), now), "2 weeks ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::days(400), now), "1 year ago");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![
+            vec!["Horizon".to_string(), "3 days ago".to_string()],
+            vec!["fbcli".to_string(), "just now".to_string()],
+        ];
+        let table = render_table(&["Repository", "Updated"], &rows);
+        assert_eq!(
+            table,
+            "Repository  Updated\n----------  ----------\nHorizon     3 days ago\nfbcli       just now"
+        );
+    }
+
+    #[test]
+    fn test_parse_github_remote() {
+        let expected = GitHubRemote {
+            host: "github.com".to_string(),
+            owner: "Far-Beyond-Dev".to_string(),
+            repo: "Horizon".to_string(),
+        };
+        assert_eq!(parse_github_remote("https://github.com/Far-Beyond-Dev/Horizon.git"), Some(expected.clone()));
+        assert_eq!(parse_github_remote("https://github.com/Far-Beyond-Dev/Horizon"), Some(expected.clone()));
+        assert_eq!(parse_github_remote("https://user@github.com/Far-Beyond-Dev/Horizon/"), Some(expected.clone()));
+        assert_eq!(parse_github_remote("git@github.com:Far-Beyond-Dev/Horizon.git"), Some(expected.clone()));
+        assert_eq!(parse_github_remote("ssh://git@github.com:22/Far-Beyond-Dev/Horizon.git"), Some(expected));
+
+        let remote = parse_github_remote("https://github.com/Far-Beyond-Dev/Horizon").unwrap();
+        assert_eq!(remote.ssh_url(), "git@github.com:Far-Beyond-Dev/Horizon.git");
+        assert_eq!(remote.https_url(), "https://github.com/Far-Beyond-Dev/Horizon.git");
+        assert_eq!(remote.web_url(), "https://github.com/Far-Beyond-Dev/Horizon");
+
+        assert!(parse_github_remote("/tmp/local/repo.git").is_none());
+        assert!(parse_github_remote("https://github.com/Far-Beyond-Dev").is_none());
+        assert!(parse_github_remote("not a url").is_none());
     }
 
     #[test]
@@ -116,4 +437,23 @@ mod tests {
         assert_eq!(format_bytes(1536), "1.5 KB");
         assert_eq!(format_bytes(1048576), "1.0 MB");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(parse_git_version("git version 2.43.0\n"), Some((2, 43, 0)));
+        assert_eq!(parse_git_version("git version 2.39.3 (Apple Git-145)"), Some((2, 39, 3)));
+        assert_eq!(parse_git_version("git version 2.45.1.windows.1"), Some((2, 45, 1)));
+        assert_eq!(parse_git_version("not git"), None);
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}